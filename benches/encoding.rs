@@ -0,0 +1,91 @@
+//! 比较逐个例子编码与后缀树编码（`LearnOptions::encoding`）在大量例子上的学习时间
+//!
+//! 运行 `cargo bench --bench encoding`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use limeltl::{learn, Encoding, Input, LearnOptions};
+use std::convert::TryFrom;
+
+const VOCAB: [&str; 3] = ["p", "q", "r"];
+
+/// 线性同余生成器，保证每次运行的例子相同
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+}
+
+/// `p U q` 是否在序列上成立
+fn until(trace: &[Vec<&str>]) -> bool {
+    for step in trace.iter() {
+        if step.contains(&"q") {
+            return true;
+        }
+        if !step.contains(&"p") {
+            return false;
+        }
+    }
+    false
+}
+
+/// `count` 个随机例子，以 `p U q` 区分正例与反例；与日志类似，序列的结尾取自少数几种后缀
+fn traces(count: usize) -> String {
+    let mut rng = Lcg(count as u64);
+    let step = |rng: &mut Lcg| -> Vec<&str> {
+        VOCAB.iter().copied().filter(|_| rng.next(2) == 0).collect()
+    };
+    let endings: Vec<Vec<Vec<&str>>> = (0..4)
+        .map(|_| (0..6).map(|_| step(&mut rng)).collect())
+        .collect();
+    let (mut pos, mut neg) = (vec![], vec![]);
+    for _ in 0..count {
+        let mut trace: Vec<Vec<&str>> = (0..rng.next(4)).map(|_| step(&mut rng)).collect();
+        trace.extend(endings[rng.next(endings.len())].iter().cloned());
+        if until(&trace) {
+            pos.push(trace);
+        } else {
+            neg.push(trace);
+        }
+    }
+    serde_json::json!({
+        "vocab": VOCAB,
+        "traces_pos": pos,
+        "traces_neg": neg,
+    })
+    .to_string()
+}
+
+fn large_trace_sets(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large_trace_sets");
+    group.sample_size(10);
+    for &count in [100, 400].iter() {
+        let json = traces(count);
+        for &encoding in [Encoding::Example, Encoding::Trie].iter() {
+            group.bench_with_input(
+                BenchmarkId::new(encoding.to_string(), count),
+                &json,
+                |b, json| {
+                    b.iter(|| {
+                        let opts = LearnOptions {
+                            max_size: 4,
+                            encoding,
+                            ..LearnOptions::default()
+                        };
+                        let result = learn(Input::try_from(json.as_str()).unwrap(), opts).unwrap();
+                        assert_eq!(result.size, 3);
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, large_trace_sets);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+//! 比较 `LearnOptions::symmetry_breaking` 对不可满足的尺寸限制的求解时间的影响
+//!
+//! 运行 `cargo bench --bench symmetry`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use limeltl::{learn, Input, LearnError, LearnOptions};
+use std::convert::TryFrom;
+
+/// 在尺寸限制 `2..=6` 内均不可满足的随机例子
+const UNSAT: &str = r#"{
+    "vocab": ["p", "q", "r"],
+    "traces_pos": [
+        [[], ["p", "r"], ["p", "r"], ["p"], ["p", "q", "r"]],
+        [["p", "q"], [], [], ["p", "q", "r"]],
+        [["p", "r"], [], ["q", "r"]],
+        [["r"], ["r"], ["p"]],
+        [[], ["p"], ["p", "q"], ["q"]]
+    ],
+    "traces_neg": [
+        [["p"], [], ["p", "r"]],
+        [[], [], [], []],
+        [[], [], ["q"], ["q", "r"], ["p", "r"]],
+        [["r"], ["p"]],
+        [["p"], ["p", "q", "r"], [], [], []]
+    ]
+}"#;
+
+fn unsat_bounds(c: &mut Criterion) {
+    let mut group = c.benchmark_group("unsat_bounds");
+    group.sample_size(10);
+    for &incremental in [false, true].iter() {
+        for &symmetry_breaking in [false, true].iter() {
+            let id = BenchmarkId::new(
+                if symmetry_breaking { "symmetry" } else { "plain" },
+                if incremental { "incremental" } else { "fresh" },
+            );
+            group.bench_function(id, |b| {
+                b.iter(|| {
+                    let opts = LearnOptions {
+                        max_size: 6,
+                        incremental,
+                        symmetry_breaking,
+                        ..LearnOptions::default()
+                    };
+                    let result = learn(Input::try_from(UNSAT).unwrap(), opts);
+                    assert!(matches!(result, Err(LearnError::Unsatisfiable(6))));
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, unsat_bounds);
+criterion_main!(benches);
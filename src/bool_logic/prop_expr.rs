@@ -91,9 +91,9 @@ impl std::fmt::Debug for BinaryOp {
     }
 }
 
-impl Into<PropExpr> for Variable {
-    fn into(self) -> PropExpr {
-        PropExpr::var(self)
+impl From<Variable> for PropExpr {
+    fn from(val: Variable) -> Self {
+        PropExpr::var(val)
     }
 }
 
@@ -16,10 +16,34 @@ pub enum Variable {
     Eventually(usize),
     /// skeleton `.0` is logic `G a`
     Always(usize),
+    /// skeleton `.0` is logic `Y a`
+    Yesterday(usize),
+    /// skeleton `.0` is logic `Z a`
+    WYesterday(usize),
+    /// skeleton `.0` is logic `a S b`
+    Since(usize),
+    /// skeleton `.0` is logic `a T b`
+    Trigger(usize),
+    /// skeleton `.0` is logic `O a`
+    Once(usize),
+    /// skeleton `.0` is logic `H a`
+    Historically(usize),
+    /// skeleton `.0` is logic `!a`
+    Not(usize),
+    /// skeleton `.0` is logic `a -> b`
+    Implies(usize),
+    /// skeleton `.0` is logic `true`
+    True(usize),
+    /// skeleton `.0` is logic `false`
+    False(usize),
     /// skeleton `.0` is logic `p`
     Literal(usize),
     /// Run(e, t, s)
     Run(usize, usize, usize),
+    /// 例子 `.0` 取相反极性时的 Run(e, t, s)，用于编码 `!` 与 `->`
+    FlipRun(usize, usize, usize),
+    /// 后缀树节点 `.0` 表示的后缀满足节点 `.1` 的子树，正例与反例共用
+    TrieRun(usize, usize),
     /// `.0` has left child `.1`
     LeftChild(usize, usize),
     /// `.0` has right child `.1`
@@ -28,32 +52,48 @@ pub enum Variable {
     Word(usize, usize, bool),
     /// Exactly `true` or `false`
     Exactly(bool),
+    /// 尺寸限制为 `.0`，用于激活只在该尺寸限制下成立的规则
+    Bound(usize),
+    /// 允许例子 `.0` 不被满足
+    Relax(usize),
+    /// 例子 `0..=.0` 中至少有 `.1 + 1` 个不被满足
+    Counter(usize, usize),
     /// 用于简化求解的无关变量
     Phantom(usize),
 }
 
 impl Variable {
     pub fn is_atom(&self) -> bool {
-        if let Variable::Literal(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(
+            self,
+            Variable::Literal(_) | Variable::True(_) | Variable::False(_)
+        )
     }
     pub fn is_unary(&self) -> bool {
-        match self {
+        matches!(
+            self,
             Variable::Eventually(_)
-            | Variable::Next(_)
-            | Variable::WNext(_)
-            | Variable::Always(_) => true,
-            _ => false,
-        }
+                | Variable::Next(_)
+                | Variable::WNext(_)
+                | Variable::Always(_)
+                | Variable::Yesterday(_)
+                | Variable::WYesterday(_)
+                | Variable::Once(_)
+                | Variable::Historically(_)
+                | Variable::Not(_)
+        )
     }
     pub fn is_binary(&self) -> bool {
-        match self {
-            Variable::And(_) | Variable::Or(_) | Variable::Until(_) | Variable::Release(_) => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            Variable::And(_)
+                | Variable::Or(_)
+                | Variable::Until(_)
+                | Variable::Release(_)
+                | Variable::Since(_)
+                | Variable::Trigger(_)
+                | Variable::Implies(_)
+        )
     }
     pub fn skeleton_id(&self) -> usize {
         match self {
@@ -65,6 +105,16 @@ impl Variable {
             | Variable::Release(id)
             | Variable::Eventually(id)
             | Variable::Always(id)
+            | Variable::Yesterday(id)
+            | Variable::WYesterday(id)
+            | Variable::Since(id)
+            | Variable::Trigger(id)
+            | Variable::Once(id)
+            | Variable::Historically(id)
+            | Variable::Not(id)
+            | Variable::Implies(id)
+            | Variable::True(id)
+            | Variable::False(id)
             | Variable::Literal(id) => id.to_owned(),
             _ => panic!("variable {:?} is not skeleton", self),
         }
@@ -82,8 +132,20 @@ impl std::fmt::Debug for Variable {
             Variable::Release(s) => f.write_fmt(format_args!("RELEASE({})", s)),
             Variable::Eventually(s) => f.write_fmt(format_args!("EVENTUALLY({})", s)),
             Variable::Always(s) => f.write_fmt(format_args!("ALWAYS({})", s)),
+            Variable::Yesterday(s) => f.write_fmt(format_args!("YESTERDAY({})", s)),
+            Variable::WYesterday(s) => f.write_fmt(format_args!("WYESTERDAY({})", s)),
+            Variable::Since(s) => f.write_fmt(format_args!("SINCE({})", s)),
+            Variable::Trigger(s) => f.write_fmt(format_args!("TRIGGER({})", s)),
+            Variable::Once(s) => f.write_fmt(format_args!("ONCE({})", s)),
+            Variable::Historically(s) => f.write_fmt(format_args!("HISTORICALLY({})", s)),
+            Variable::Not(s) => f.write_fmt(format_args!("NOT({})", s)),
+            Variable::Implies(s) => f.write_fmt(format_args!("IMPLIES({})", s)),
+            Variable::True(s) => f.write_fmt(format_args!("TRUE({})", s)),
+            Variable::False(s) => f.write_fmt(format_args!("FALSE({})", s)),
             Variable::Literal(s) => f.write_fmt(format_args!("LIT({})", s)),
             Variable::Run(e, t, s) => f.write_fmt(format_args!("RUN({}, {}, {})", e, t, s)),
+            Variable::FlipRun(e, t, s) => f.write_fmt(format_args!("FLIPRUN({}, {}, {})", e, t, s)),
+            Variable::TrieRun(n, s) => f.write_fmt(format_args!("TRIERUN({}, {})", n, s)),
             Variable::LeftChild(s, s1) => f.write_fmt(format_args!("A({}, {})", s, s1)),
             Variable::RightChild(s, s1) => f.write_fmt(format_args!("B({}, {})", s, s1)),
             Variable::Word(s, v, p) => f.write_fmt(format_args!(
@@ -93,6 +155,9 @@ impl std::fmt::Debug for Variable {
                 v
             )),
             Variable::Exactly(v) => f.write_fmt(format_args!("{}", v)),
+            Variable::Bound(n) => f.write_fmt(format_args!("BOUND({})", n)),
+            Variable::Relax(e) => f.write_fmt(format_args!("RELAX({})", e)),
+            Variable::Counter(e, k) => f.write_fmt(format_args!("COUNTER({}, {})", e, k)),
             Variable::Phantom(i) => f.write_fmt(format_args!("PHANTOM({})", i)),
         }
     }
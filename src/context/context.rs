@@ -1,18 +1,35 @@
-use super::{Example, Word};
+use super::{Example, SuffixTrie, Weight, Word};
+use crate::ltl::Operator;
 use std::collections::{HashMap, HashSet};
 
+#[derive(Clone)]
 pub struct Context {
     max_skeletons: usize,
+    encoded_skeletons: usize,
+    max_errors: Option<usize>,
+    operators: Vec<Operator>,
+    symmetry_breaking: bool,
     vocab: HashMap<String, usize>,
     examples: Vec<Example>,
+    /// 已添加的正例与反例数量，即下一个例子在输入中的序号
+    pos_traces: usize,
+    neg_traces: usize,
+    trie: Option<SuffixTrie>,
 }
 
 impl Context {
     pub fn new() -> Self {
         Self {
             max_skeletons: 0,
+            encoded_skeletons: 0,
+            max_errors: None,
+            operators: Operator::FUTURE.to_vec(),
+            symmetry_breaking: false,
             vocab: HashMap::new(),
             examples: Vec::new(),
+            pos_traces: 0,
+            neg_traces: 0,
+            trie: None,
         }
     }
 
@@ -24,11 +41,22 @@ impl Context {
     }
 }
 
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Context {
     /// Skeleton 的最大数量限制；假定其为 `N`，则可行的 Skeleton 编号为 `0 <= i < N`
     pub fn max_skeletons(&self) -> usize {
         self.max_skeletons
     }
+    /// 涉及的最大 Skeleton 编号为 `max_id` 的规则是否尚未加入求解器；增量求解时，
+    /// 假定已经编码的 Skeleton 数量为 `M`，则编号 `0 <= i < M` 的 Skeleton 之间的规则无需再次生成
+    pub fn is_fresh(&self, max_id: usize) -> bool {
+        max_id >= self.encoded_skeletons
+    }
     /// 例子中变量的最大数量；假定其为 `N`，则可行的例子变量编号为 `0 <= i < N`
     pub fn word_count(&self) -> usize {
         self.vocab.len()
@@ -41,15 +69,45 @@ impl Context {
         self.vocab.insert(word, self.vocab.len());
         self.vocab.len() - 1
     }
-    /// 添加例子，会自动创建对应的单词
-    pub fn add_example(&mut self, sequence: Vec<HashSet<Word>>, is_positive: bool) {
-        self.examples
-            .push(Example::new(self.examples.len(), sequence, is_positive))
+    /// 添加例子，会自动创建对应的单词；`lasso` 为套索序列的循环起点，有穷序列为 `None`
+    pub fn add_example(
+        &mut self,
+        sequence: Vec<HashSet<Word>>,
+        is_positive: bool,
+        weight: Weight,
+        lasso: Option<usize>,
+    ) {
+        let counter = if is_positive {
+            &mut self.pos_traces
+        } else {
+            &mut self.neg_traces
+        };
+        let trace = *counter;
+        *counter += 1;
+        self.examples.push(Example::new(
+            self.examples.len(),
+            sequence,
+            is_positive,
+            weight,
+            lasso,
+            trace,
+        ))
     }
     /// 获得所有例子
     pub fn examples(&self) -> impl Iterator<Item = &Example> {
         self.examples.iter()
     }
+    /// 预处理时修改例子，例子编号需要保持为其下标
+    pub(super) fn examples_mut(&mut self) -> &mut Vec<Example> {
+        &mut self.examples
+    }
+    /// 有穷例子的后缀树，只在调用 `build_trie` 后存在
+    pub fn trie(&self) -> Option<&SuffixTrie> {
+        self.trie.as_ref()
+    }
+    pub(super) fn set_trie(&mut self, trie: SuffixTrie) {
+        self.trie = Some(trie);
+    }
     /// 获得所有单词
     pub fn words(&self) -> &HashMap<String, usize> {
         &self.vocab
@@ -58,4 +116,45 @@ impl Context {
     pub fn set_size_bound(&mut self, bound: usize) {
         self.max_skeletons = bound;
     }
+    /// 是否允许例子不被满足，见 `Variable::Relax`
+    pub fn is_noise_tolerant(&self) -> bool {
+        self.max_errors.is_some()
+    }
+    /// 所有允许不被满足的例子的代价之和
+    pub fn total_cost(&self) -> usize {
+        self.examples.iter().map(|e| e.weight().cost()).sum()
+    }
+    /// 至多允许不被满足的例子的代价之和
+    pub fn max_errors(&self) -> Option<usize> {
+        self.max_errors
+    }
+    /// 设置至多允许不被满足的例子的代价之和，`None` 表示所有例子都必须被满足
+    pub fn set_max_errors(&mut self, max_errors: Option<usize>) {
+        self.max_errors = max_errors;
+    }
+    /// 编码中可以使用的运算符
+    pub fn operators(&self) -> &[Operator] {
+        &self.operators
+    }
+    /// 设置编码中可以使用的运算符，重复的运算符只保留一个，默认为所有将来时运算符
+    pub fn set_operators(&mut self, operators: &[Operator]) {
+        self.operators.clear();
+        for op in operators {
+            if !self.operators.contains(op) {
+                self.operators.push(*op);
+            }
+        }
+    }
+    /// 是否要求节点按照广度优先的顺序编号，见 `enforcers::SymmetryEnforcer`
+    pub fn is_symmetry_breaking(&self) -> bool {
+        self.symmetry_breaking
+    }
+    /// 设置是否要求节点按照广度优先的顺序编号
+    pub fn set_symmetry_breaking(&mut self, symmetry_breaking: bool) {
+        self.symmetry_breaking = symmetry_breaking;
+    }
+    /// 设置已经编码的 Skeleton 数量
+    pub fn set_encoded_skeletons(&mut self, encoded: usize) {
+        self.encoded_skeletons = encoded;
+    }
 }
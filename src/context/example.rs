@@ -1,18 +1,61 @@
 use super::Word;
 use std::collections::HashSet;
 
+/// 例子的权重
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Weight {
+    /// 必须被满足
+    Hard,
+    /// 允许例子不被满足时，不被满足的代价
+    Soft(usize),
+}
+
+impl Default for Weight {
+    fn default() -> Self {
+        Weight::Soft(1)
+    }
+}
+
+impl Weight {
+    /// 不被满足的代价，必须被满足的例子为 `0`
+    pub fn cost(&self) -> usize {
+        match self {
+            Weight::Hard => 0,
+            Weight::Soft(w) => *w,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Example {
     id: usize,
     sequence: Vec<HashSet<Word>>,
     is_positive: bool,
+    weight: Weight,
+    lasso: Option<usize>,
+    /// 输入中对应的同类例子的序号，合并重复的例子后可能有多个
+    traces: Vec<usize>,
+    /// 每个时间点使用的 `Run` 变量所属的例子与时间，为空时总是使用自身的变量
+    runs: Vec<(usize, usize)>,
 }
 
 impl Example {
-    pub fn new(id: usize, sequence: Vec<HashSet<Word>>, is_positive: bool) -> Self {
+    pub fn new(
+        id: usize,
+        sequence: Vec<HashSet<Word>>,
+        is_positive: bool,
+        weight: Weight,
+        lasso: Option<usize>,
+        trace: usize,
+    ) -> Self {
         Self {
             id,
             sequence,
             is_positive,
+            weight,
+            lasso,
+            traces: vec![trace],
+            runs: vec![],
         }
     }
 }
@@ -30,10 +73,73 @@ impl Example {
     pub fn contains_at(&self, t: usize, v: usize) -> bool {
         self.sequence[t].contains(&Word::new_unchecked(v))
     }
+    /// 获得在给定时间包含的所有变量
+    pub fn words_at(&self, t: usize) -> impl Iterator<Item = usize> + '_ {
+        self.sequence[t].iter().map(|w| w.id())
+    }
     /// 返回其为正例还是反例
     pub fn is_pos(&self) -> bool {
         self.is_positive
     }
+    /// 获得例子的权重
+    pub fn weight(&self) -> Weight {
+        self.weight
+    }
+    /// 套索序列的循环起点，有穷序列为 `None`
+    pub fn lasso(&self) -> Option<usize> {
+        self.lasso
+    }
+    /// 输入中对应的同类例子的序号，按从小到大的顺序
+    pub fn traces(&self) -> &[usize] {
+        &self.traces
+    }
+    /// 时间 `t` 使用的 `Run` 变量所属的例子与时间，见 `Context::share_suffixes`
+    pub fn run_at(&self, t: usize) -> (usize, usize) {
+        match self.runs.get(t) {
+            Some(run) => *run,
+            None => (self.id, t),
+        }
+    }
+    /// 时间 `t` 是否使用其它例子的 `Run` 变量，此时该时间点的规则由其它例子生成
+    pub fn is_shared_at(&self, t: usize) -> bool {
+        self.run_at(t).0 != self.id
+    }
+    /// 时间 `t` 的后继：有穷序列的最后一个时间点没有后继，套索序列的最后一个时间点回到循环起点
+    pub fn next(&self, t: usize) -> Option<usize> {
+        if t + 1 < self.size() {
+            Some(t + 1)
+        } else {
+            self.lasso
+        }
+    }
+    /// 从时间 `t` 出发依次经过的所有时间点，每个时间点只出现一次
+    pub fn reach(&self, t: usize) -> impl Iterator<Item = usize> {
+        let wrap = match self.lasso {
+            Some(l) if l < t => l..t,
+            _ => 0..0,
+        };
+        (t..self.size()).chain(wrap)
+    }
+}
+
+impl Example {
+    /// 将相同序列的同类例子 `other` 合并到该例子，见 `Context::merge_duplicates`
+    pub(super) fn merge(&mut self, other: Example) {
+        self.weight = match (self.weight, other.weight) {
+            (Weight::Soft(a), Weight::Soft(b)) => Weight::Soft(a + b),
+            _ => Weight::Hard,
+        };
+        self.traces.extend(other.traces);
+        self.traces.sort_unstable();
+    }
+
+    pub(super) fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
+    pub(super) fn set_runs(&mut self, runs: Vec<(usize, usize)>) {
+        self.runs = runs;
+    }
 }
 
 impl PartialEq for Example {
@@ -51,6 +157,9 @@ impl std::fmt::Debug for Example {
                 for v in self.sequence.iter() {
                     dt.field(v);
                 }
+                if let Some(l) = self.lasso {
+                    dt.field(&format_args!("loop {}", l));
+                }
                 dt.finish()
             })
     }
@@ -1,6 +1,8 @@
 //! 输入 JSON 解析。
 //!
 //! 不能够出现重复变量的位置（单词表、例子中单个时间点变量取值）若有重复变量将被忽略。
+//! 例子中的单词必须出现在单词表中，且例子不能为空序列；宽松模式下（`Input::extend_vocab`），
+//! 单词表以外的单词将被加入单词表。
 //!
 //! # Example
 //!
@@ -20,13 +22,29 @@
 //!     ]
 //! }
 //! ```
+//!
+//! 允许例子不被满足时，可以为例子指定权重（默认为 `1`），或要求其必须被满足；
+//! 同时给出 `hard` 与 `weight` 时忽略 `weight`。
+//!
+//! ```json
+//! {
+//!     "vocab": ["p", "q"],
+//!     "traces_pos": [
+//!         {"trace": [["p"], ["q"]], "hard": true},
+//!         {"trace": [["q"]], "weight": 3},
+//!         [["p"], ["p"], ["q"]]
+//!     ],
+//!     "traces_neg": [[["p"]]]
+//! }
+//! ```
 
-use super::{Context, Word};
+use super::{Context, Contradiction, Weight, Word};
 use serde::Deserialize;
 use std::{collections::HashSet, convert::TryFrom};
 
 /// 输入的直接表示
 #[derive(Debug, Eq, PartialEq, Deserialize)]
+#[serde(from = "RawInput")]
 pub struct Input {
     /// 可用变量列表
     vocab: HashSet<String>,
@@ -34,43 +52,464 @@ pub struct Input {
     traces_pos: Vec<Vec<HashSet<String>>>,
     /// 反例
     traces_neg: Vec<Vec<HashSet<String>>>,
+    /// 正例的权重
+    weights_pos: Vec<Weight>,
+    /// 反例的权重
+    weights_neg: Vec<Weight>,
+    /// 正例的循环起点
+    lassos_pos: Vec<Option<usize>>,
+    /// 反例的循环起点
+    lassos_neg: Vec<Option<usize>>,
+}
+
+impl Input {
+    pub fn new(
+        vocab: HashSet<String>,
+        traces_pos: Vec<Vec<HashSet<String>>>,
+        traces_neg: Vec<Vec<HashSet<String>>>,
+    ) -> Self {
+        Self {
+            vocab,
+            weights_pos: vec![Weight::default(); traces_pos.len()],
+            weights_neg: vec![Weight::default(); traces_neg.len()],
+            lassos_pos: vec![None; traces_pos.len()],
+            lassos_neg: vec![None; traces_neg.len()],
+            traces_pos,
+            traces_neg,
+        }
+    }
+}
+
+/// JSON 中的例子，可以直接给出序列，也可以同时给出权重与套索序列的循环起点 `loop`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTrace {
+    Plain(Vec<HashSet<String>>),
+    Weighted {
+        trace: Vec<HashSet<String>>,
+        #[serde(default)]
+        weight: Option<usize>,
+        #[serde(default)]
+        hard: bool,
+        #[serde(default, rename = "loop")]
+        lasso: Option<usize>,
+    },
+}
+
+impl RawTrace {
+    fn into_parts(self) -> (Vec<HashSet<String>>, (Weight, Option<usize>)) {
+        match self {
+            RawTrace::Plain(trace) => (trace, (Weight::default(), None)),
+            RawTrace::Weighted {
+                trace,
+                hard: true,
+                lasso,
+                ..
+            } => (trace, (Weight::Hard, lasso)),
+            RawTrace::Weighted {
+                trace,
+                weight,
+                lasso,
+                ..
+            } => (trace, (weight.map(Weight::Soft).unwrap_or_default(), lasso)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawInput {
+    vocab: HashSet<String>,
+    traces_pos: Vec<RawTrace>,
+    traces_neg: Vec<RawTrace>,
+}
+
+impl From<RawInput> for Input {
+    fn from(raw: RawInput) -> Self {
+        let (traces_pos, (weights_pos, lassos_pos)) =
+            raw.traces_pos.into_iter().map(RawTrace::into_parts).unzip();
+        let (traces_neg, (weights_neg, lassos_neg)) =
+            raw.traces_neg.into_iter().map(RawTrace::into_parts).unzip();
+        Self {
+            vocab: raw.vocab,
+            traces_pos,
+            traces_neg,
+            weights_pos,
+            weights_neg,
+            lassos_pos,
+            lassos_neg,
+        }
+    }
+}
+
+impl Input {
+    /// 可用变量列表
+    pub fn vocab(&self) -> &HashSet<String> {
+        &self.vocab
+    }
+    /// 正例
+    pub fn traces_pos(&self) -> &[Vec<HashSet<String>>] {
+        &self.traces_pos
+    }
+    /// 反例
+    pub fn traces_neg(&self) -> &[Vec<HashSet<String>>] {
+        &self.traces_neg
+    }
+    /// 正例的权重
+    pub fn weights_pos(&self) -> &[Weight] {
+        &self.weights_pos
+    }
+    /// 反例的权重
+    pub fn weights_neg(&self) -> &[Weight] {
+        &self.weights_neg
+    }
+    /// 正例的循环起点，有穷序列为 `None`
+    pub fn lassos_pos(&self) -> &[Option<usize>] {
+        &self.lassos_pos
+    }
+    /// 反例的循环起点，有穷序列为 `None`
+    pub fn lassos_neg(&self) -> &[Option<usize>] {
+        &self.lassos_neg
+    }
+    /// 是否存在套索序列
+    pub fn has_lasso(&self) -> bool {
+        self.lassos_pos
+            .iter()
+            .chain(self.lassos_neg.iter())
+            .any(Option::is_some)
+    }
+}
+
+impl Input {
+    /// 从 JSON 读取输入
+    pub fn from_reader(reader: impl std::io::Read) -> Result<Self, InputError> {
+        serde_json::from_reader(reader).map_err(InputError::Json)
+    }
 }
 
 impl TryFrom<&str> for Input {
     type Error = serde_json::Error;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        serde_json::from_str(&value)
+        serde_json::from_str(value)
     }
 }
 
-impl Into<Context> for Input {
-    fn into(self) -> Context {
+/// 例子中出现的单词表以外的单词
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UnknownWord {
+    /// 是否出现在正例中
+    pub positive: bool,
+    /// 例子序号
+    pub trace: usize,
+    /// 时间
+    pub time: usize,
+    /// 单词
+    pub word: String,
+}
+
+impl std::fmt::Display for UnknownWord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "第 {} 个{}在时间 {} 出现了单词 {:?}",
+            self.trace,
+            if self.positive { "正例" } else { "反例" },
+            self.time,
+            self.word
+        )
+    }
+}
+
+impl Input {
+    /// 按照例子、时间的顺序列出所有单词表以外的单词
+    pub fn unknown_words(&self) -> Vec<UnknownWord> {
+        let traces = self
+            .traces_pos
+            .iter()
+            .enumerate()
+            .map(|(i, trace)| (true, i, trace))
+            .chain(
+                self.traces_neg
+                    .iter()
+                    .enumerate()
+                    .map(|(i, trace)| (false, i, trace)),
+            );
+        let mut result = Vec::new();
+        for (positive, trace, seq) in traces {
+            for (time, step) in seq.iter().enumerate() {
+                let mut words: Vec<&String> =
+                    step.iter().filter(|w| !self.vocab.contains(*w)).collect();
+                words.sort();
+                result.extend(words.into_iter().map(|word| UnknownWord {
+                    positive,
+                    trace,
+                    time,
+                    word: word.clone(),
+                }));
+            }
+        }
+        result
+    }
+
+    /// 宽松模式：将例子中出现的单词表以外的单词加入单词表，返回所有被加入的单词的出现位置
+    pub fn extend_vocab(&mut self) -> Vec<UnknownWord> {
+        let unknown = self.unknown_words();
+        self.vocab.extend(unknown.iter().map(|w| w.word.clone()));
+        unknown
+    }
+
+    /// 检查输入是否合法：例子不能为空序列，循环起点必须在序列之内，且只能包含单词表中的单词
+    pub fn validate(&self) -> Result<(), InputError> {
+        let empty = self
+            .traces_pos
+            .iter()
+            .enumerate()
+            .map(|(i, trace)| (true, i, trace))
+            .chain(
+                self.traces_neg
+                    .iter()
+                    .enumerate()
+                    .map(|(i, trace)| (false, i, trace)),
+            )
+            .find(|(_, _, trace)| trace.is_empty());
+        if let Some((positive, trace, _)) = empty {
+            return Err(InputError::EmptyTrace { positive, trace });
+        }
+        let lassos = self
+            .traces_pos
+            .iter()
+            .zip(self.lassos_pos.iter())
+            .enumerate()
+            .map(|(i, (trace, l))| (true, i, trace, l))
+            .chain(
+                self.traces_neg
+                    .iter()
+                    .zip(self.lassos_neg.iter())
+                    .enumerate()
+                    .map(|(i, (trace, l))| (false, i, trace, l)),
+            );
+        for (positive, trace, seq, lasso) in lassos {
+            if let Some(lasso) = *lasso {
+                if lasso >= seq.len() {
+                    return Err(InputError::InvalidLoop {
+                        positive,
+                        trace,
+                        lasso,
+                    });
+                }
+            }
+        }
+        let unknown = self.unknown_words();
+        if !unknown.is_empty() {
+            return Err(InputError::UnknownWords(unknown));
+        }
+        Ok(())
+    }
+}
+
+/// 输入不合法
+#[derive(Debug)]
+pub enum InputError {
+    /// 无法解析 JSON
+    Json(serde_json::Error),
+    /// 例子中出现了单词表以外的单词，列出所有出现位置
+    UnknownWords(Vec<UnknownWord>),
+    /// 第 `trace` 个正例（反例）为空序列
+    EmptyTrace { positive: bool, trace: usize },
+    /// 第 `trace` 个正例（反例）的循环起点 `lasso` 超出序列长度
+    InvalidLoop {
+        positive: bool,
+        trace: usize,
+        lasso: usize,
+    },
+    /// 第 `trace` 个正例（反例）为套索序列，但使用了过去时运算符
+    PastTimeLasso { positive: bool, trace: usize },
+    /// 以后缀树编码例子（`Encoding::Trie`），但使用了过去时运算符
+    PastTimeTrie,
+    /// 存在同时作为正例与反例出现的序列，且不允许例子不被满足，或双方都有必须被满足的例子
+    Contradictory(Vec<Contradiction>),
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Json(e) => write!(f, "无法解析 JSON：{}", e),
+            InputError::UnknownWords(words) => {
+                write!(f, "例子中出现了 {} 处单词表以外的单词", words.len())?;
+                for w in words.iter() {
+                    write!(f, "\n  {}", w)?;
+                }
+                Ok(())
+            }
+            InputError::EmptyTrace { positive, trace } => write!(
+                f,
+                "第 {} 个{}为空序列",
+                trace,
+                if *positive { "正例" } else { "反例" }
+            ),
+            InputError::InvalidLoop {
+                positive,
+                trace,
+                lasso,
+            } => write!(
+                f,
+                "第 {} 个{}的循环起点 {} 超出序列长度",
+                trace,
+                if *positive { "正例" } else { "反例" },
+                lasso
+            ),
+            InputError::PastTimeLasso { positive, trace } => write!(
+                f,
+                "第 {} 个{}为套索序列，不支持过去时运算符",
+                trace,
+                if *positive { "正例" } else { "反例" }
+            ),
+            InputError::PastTimeTrie => f.write_str("以后缀树编码例子时不支持过去时运算符"),
+            InputError::Contradictory(contradictions) => {
+                write!(f, "{} 个序列同时作为正例与反例出现", contradictions.len())?;
+                if contradictions.iter().all(|c| !c.hard) {
+                    f.write_str("，需要允许例子不被满足")?;
+                }
+                for c in contradictions.iter() {
+                    write!(f, "\n  {}", c)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// 将例子转换为单词序号的序列
+fn make_sequence(ctx: &mut Context, trace: Vec<HashSet<String>>) -> Vec<HashSet<Word>> {
+    trace
+        .into_iter()
+        .map(|t| {
+            t.into_iter()
+                .map(|s| Word::new_unchecked(ctx.get_word_id(s)))
+                .collect()
+        })
+        .collect()
+}
+
+impl TryFrom<Input> for Context {
+    type Error = InputError;
+    /// 严格转换，输入不合法时报告错误；如需接受单词表以外的单词，应先调用 `Input::extend_vocab`
+    fn try_from(input: Input) -> Result<Self, Self::Error> {
+        input.validate()?;
         let mut ctx = Context::new();
-        for word in self.vocab {
-            ctx.get_word_id(word); // 通过获取单词序号来创建单词
+        // 按名称顺序创建单词，使相同的输入总是得到相同的编码
+        let mut vocab: Vec<&String> = input.vocab.iter().collect();
+        vocab.sort();
+        for word in vocab.into_iter() {
+            ctx.get_word_id(word.clone()); // 通过获取单词序号来创建单词
         }
-        for pos_ex in self.traces_pos.into_iter() {
-            let seq = pos_ex
-                .into_iter()
-                .map(|t| {
-                    t.into_iter()
-                        .map(|s| Word::new_unchecked(ctx.get_word_id(s)))
-                        .collect()
-                })
-                .collect();
-            ctx.add_example(seq, true);
+        let pos = input.weights_pos.into_iter().zip(input.lassos_pos);
+        for (pos_ex, (weight, lasso)) in input.traces_pos.into_iter().zip(pos) {
+            let seq = make_sequence(&mut ctx, pos_ex);
+            ctx.add_example(seq, true, weight, lasso);
+        }
+        let neg = input.weights_neg.into_iter().zip(input.lassos_neg);
+        for (neg_ex, (weight, lasso)) in input.traces_neg.into_iter().zip(neg) {
+            let seq = make_sequence(&mut ctx, neg_ex);
+            ctx.add_example(seq, false, weight, lasso);
+        }
+        Ok(ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalid_input() {
+        let input = Input::try_from(
+            r#"{"vocab":["p"],"traces_pos":[[["p"],["q"]]],"traces_neg":[[["r","pp","p"]]]}"#,
+        );
+        match Context::try_from(input.unwrap()) {
+            Err(InputError::UnknownWords(words)) => assert_eq!(
+                words
+                    .iter()
+                    .map(|w| (w.positive, w.trace, w.time, w.word.as_str()))
+                    .collect::<Vec<_>>(),
+                vec![(true, 0, 1, "q"), (false, 0, 0, "pp"), (false, 0, 0, "r")]
+            ),
+            _ => panic!("应当拒绝单词表以外的单词"),
         }
-        for neg_ex in self.traces_neg.into_iter() {
-            let seq = neg_ex
-                .into_iter()
-                .map(|t| {
-                    t.into_iter()
-                        .map(|s| Word::new_unchecked(ctx.get_word_id(s)))
-                        .collect()
-                })
-                .collect();
-            ctx.add_example(seq, false);
+
+        let input = Input::try_from(r#"{"vocab":["p"],"traces_pos":[],"traces_neg":[[["p"]],[]]}"#);
+        match Context::try_from(input.unwrap()) {
+            Err(InputError::EmptyTrace {
+                positive: false,
+                trace: 1,
+            }) => {}
+            _ => panic!("应当拒绝空序列"),
         }
-        ctx
+
+        assert!(matches!(
+            Input::from_reader(r#"{"vocab":"#.as_bytes()),
+            Err(InputError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn extend_vocab() {
+        let mut input = Input::try_from(
+            r#"{"vocab":["p"],"traces_pos":[[["p"],["q"]],[["q"]]],"traces_neg":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(input.extend_vocab().len(), 2);
+        assert!(input.unknown_words().is_empty());
+        assert_eq!(Context::try_from(input).unwrap().word_count(), 2);
+    }
+
+    #[test]
+    fn weights() {
+        let input = Input::try_from(
+            r#"{
+                "vocab": ["p"],
+                "traces_pos": [[["p"]], {"trace": [["p"]], "hard": true}, {"trace": [[]], "weight": 3}],
+                "traces_neg": [{"trace": [[]], "hard": true, "weight": 2}, {"trace": [[]]}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            input.weights_pos(),
+            &[Weight::Soft(1), Weight::Hard, Weight::Soft(3)]
+        );
+        assert_eq!(input.weights_neg(), &[Weight::Hard, Weight::Soft(1)]);
+        assert_eq!(input.traces_pos()[2], vec![HashSet::new()]);
+    }
+
+    #[test]
+    fn lasso() {
+        let input = Input::try_from(
+            r#"{
+                "vocab": ["p"],
+                "traces_pos": [{"trace": [[], ["p"]], "loop": 1}, [["p"]]],
+                "traces_neg": [{"trace": [[]], "weight": 2}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(input.lassos_pos(), &[Some(1), None]);
+        assert_eq!(input.lassos_neg(), &[None]);
+        assert!(input.has_lasso());
+        let ctx = Context::try_from(input).unwrap();
+        let ex = ctx.examples().next().unwrap();
+        assert_eq!(ex.next(1), Some(1));
+        assert_eq!(ex.reach(1).collect::<Vec<_>>(), vec![1]);
+
+        let input = Input::try_from(
+            r#"{"vocab":["p"],"traces_pos":[],"traces_neg":[{"trace": [[], ["p"]], "loop": 2}]}"#,
+        );
+        assert!(matches!(
+            input.unwrap().validate(),
+            Err(InputError::InvalidLoop {
+                positive: false,
+                trace: 0,
+                lasso: 2
+            })
+        ));
     }
 }
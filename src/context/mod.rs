@@ -1,11 +1,27 @@
+#[allow(clippy::module_inception)]
 mod context;
 pub use context::Context;
 
 mod example;
-pub use example::Example;
+pub use example::{Example, Weight};
 
 mod input;
-pub use input::Input;
+pub use input::{Input, InputError, UnknownWord};
+
+mod preprocess;
+pub use preprocess::Contradiction;
+
+mod trie;
+pub use trie::SuffixTrie;
 
 mod word;
 pub use word::Word;
+
+/// 测试中以每个时间点的单词序号构造序列
+#[cfg(test)]
+pub(crate) fn trace(steps: &[&[usize]]) -> Vec<std::collections::HashSet<Word>> {
+    steps
+        .iter()
+        .map(|step| step.iter().map(|w| Word::new_unchecked(*w)).collect())
+        .collect()
+}
@@ -0,0 +1,206 @@
+//! 例子的预处理：检查矛盾的例子、合并重复的例子，以及在后缀相同的例子之间共享 `Run` 变量。
+//!
+//! 只有序列与循环起点都相同的例子才被视为相同，表示同一无穷序列的不同套索（如展开一次循环）不会被识别。
+
+use super::{trie::SuffixTrie, Context, Example, Weight};
+use std::collections::{hash_map::Entry, HashMap};
+
+/// 例子的序列与循环起点，每个时间点的单词按序号排列
+type TraceKey = (Vec<Vec<usize>>, Option<usize>);
+
+pub(super) fn words_at(ex: &Example, t: usize) -> Vec<usize> {
+    let mut words: Vec<usize> = ex.words_at(t).collect();
+    words.sort_unstable();
+    words
+}
+
+fn trace_key(ex: &Example) -> TraceKey {
+    let sequence = (0..ex.size()).map(|t| words_at(ex, t)).collect();
+    (sequence, ex.lasso())
+}
+
+/// 同时作为正例与反例出现的序列
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Contradiction {
+    /// 输入中对应的正例序号
+    pub pos: Vec<usize>,
+    /// 输入中对应的反例序号
+    pub neg: Vec<usize>,
+    /// 正例与反例中都有必须被满足的例子，即使允许例子不被满足也无法求得公式
+    pub hard: bool,
+}
+
+impl std::fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |traces: &[usize]| {
+            traces
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join("、")
+        };
+        write!(
+            f,
+            "正例 {} 与反例 {} 为相同的序列",
+            join(&self.pos),
+            join(&self.neg)
+        )?;
+        if self.hard {
+            f.write_str("，且双方都有必须被满足的例子")?;
+        }
+        Ok(())
+    }
+}
+
+impl Context {
+    /// 按第一次出现的顺序列出同时作为正例与反例出现的序列；不允许例子不被满足时无法求得公式，
+    /// 见 `Contradiction::hard`
+    pub fn contradictions(&self) -> Vec<Contradiction> {
+        let mut groups: HashMap<TraceKey, usize> = HashMap::new();
+        let mut found: Vec<Contradiction> = vec![];
+        // 每个序列的正例与反例中是否有必须被满足的例子
+        let mut hard: Vec<(bool, bool)> = vec![];
+        for ex in self.examples() {
+            let i = *groups.entry(trace_key(ex)).or_insert_with(|| {
+                found.push(Contradiction {
+                    pos: vec![],
+                    neg: vec![],
+                    hard: false,
+                });
+                hard.push((false, false));
+                found.len() - 1
+            });
+            let (traces, hard) = if ex.is_pos() {
+                (&mut found[i].pos, &mut hard[i].0)
+            } else {
+                (&mut found[i].neg, &mut hard[i].1)
+            };
+            traces.extend_from_slice(ex.traces());
+            *hard |= ex.weight() == Weight::Hard;
+        }
+        for (c, (pos, neg)) in found.iter_mut().zip(hard) {
+            c.hard = pos && neg;
+        }
+        found.retain(|c| !c.pos.is_empty() && !c.neg.is_empty());
+        for c in found.iter_mut() {
+            c.pos.sort_unstable();
+            c.neg.sort_unstable();
+        }
+        found
+    }
+
+    /// 合并序列与循环起点都相同的同类例子，返回被合并到其它例子的例子数量；应在
+    /// `share_suffixes` 之前调用
+    ///
+    /// 合并后的例子对应所有被合并的输入例子。允许例子不被满足时，其代价为被合并的例子的代价之和；
+    /// 若其中之一必须被满足，则合并后的例子也必须被满足。
+    pub fn merge_duplicates(&mut self) -> usize {
+        let examples = std::mem::take(self.examples_mut());
+        let before = examples.len();
+        let mut index: HashMap<(bool, TraceKey), usize> = HashMap::new();
+        let mut merged: Vec<Example> = vec![];
+        for mut ex in examples.into_iter() {
+            match index.entry((ex.is_pos(), trace_key(&ex))) {
+                Entry::Occupied(e) => merged[*e.get()].merge(ex),
+                Entry::Vacant(e) => {
+                    e.insert(merged.len());
+                    ex.set_id(merged.len());
+                    merged.push(ex);
+                }
+            }
+        }
+        let count = before - merged.len();
+        *self.examples_mut() = merged;
+        count
+    }
+
+    /// 在后缀相同的同类有穷例子之间共享 `Run` 变量，返回使用其它例子的变量的时间点数量
+    ///
+    /// 只使用将来时运算符时，`Run(e, t, s)` 只取决于例子从时间 `t` 开始的后缀，因此后缀相同的时间点
+    /// 可以使用第一个具有该后缀的例子的变量，其规则也只需生成一次。过去时运算符与之前的时间点有关，
+    /// 使用时不应调用；套索序列不参与共享。
+    pub fn share_suffixes(&mut self) -> usize {
+        // 后缀的编号，由当前时间点的单词与之后的后缀的编号确定
+        let mut suffixes = SuffixTrie::default();
+        // 每个后缀第一次出现的例子与时间
+        let mut owners: HashMap<(bool, usize), (usize, usize)> = HashMap::new();
+        let mut shared = 0;
+        for ex in self.examples_mut().iter_mut() {
+            if ex.lasso().is_some() {
+                continue;
+            }
+            let runs: Vec<_> = suffixes
+                .insert(ex)
+                .into_iter()
+                .enumerate()
+                .map(|(t, suffix)| *owners.entry((ex.is_pos(), suffix)).or_insert((ex.id(), t)))
+                .collect();
+            let count = runs.iter().filter(|(e, _)| *e != ex.id()).count();
+            if count > 0 {
+                shared += count;
+                ex.set_runs(runs);
+            }
+        }
+        shared
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::trace;
+
+    #[test]
+    fn merge() {
+        let mut ctx = Context::with_bound(2);
+        ctx.add_example(trace(&[&[0], &[1]]), true, Weight::Soft(2), None);
+        ctx.add_example(trace(&[&[1]]), true, Weight::default(), None);
+        ctx.add_example(trace(&[&[0], &[1]]), true, Weight::Soft(3), None);
+        ctx.add_example(trace(&[&[0], &[1]]), true, Weight::Soft(3), Some(1));
+        ctx.add_example(trace(&[&[1]]), false, Weight::default(), None);
+        ctx.add_example(trace(&[&[1]]), true, Weight::Hard, None);
+        assert_eq!(
+            ctx.contradictions(),
+            vec![Contradiction {
+                pos: vec![1, 4],
+                neg: vec![0],
+                hard: false
+            }]
+        );
+        assert_eq!(ctx.merge_duplicates(), 2);
+        let merged: Vec<_> = ctx
+            .examples()
+            .map(|e| (e.id(), e.is_pos(), e.traces().to_vec(), e.weight()))
+            .collect();
+        assert_eq!(
+            merged,
+            vec![
+                (0, true, vec![0, 2], Weight::Soft(5)),
+                (1, true, vec![1, 4], Weight::Hard),
+                (2, true, vec![3], Weight::Soft(3)),
+                (3, false, vec![0], Weight::default()),
+            ]
+        );
+        assert_eq!(ctx.contradictions().len(), 1);
+    }
+
+    #[test]
+    fn share() {
+        let mut ctx = Context::with_bound(2);
+        ctx.add_example(trace(&[&[0], &[1], &[0, 1]]), true, Weight::default(), None);
+        ctx.add_example(trace(&[&[1], &[1], &[1, 0]]), true, Weight::default(), None);
+        ctx.add_example(trace(&[&[1], &[0, 1]]), false, Weight::default(), None);
+        ctx.add_example(trace(&[&[1], &[0, 1]]), true, Weight::default(), Some(0));
+        assert_eq!(ctx.share_suffixes(), 2);
+        let runs = |i: usize| {
+            let ex = ctx.examples().nth(i).unwrap();
+            (0..ex.size()).map(|t| ex.run_at(t)).collect::<Vec<_>>()
+        };
+        assert_eq!(runs(0), vec![(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(runs(1), vec![(1, 0), (0, 1), (0, 2)]);
+        // 反例与套索序列不与正例共享
+        assert_eq!(runs(2), vec![(2, 0), (2, 1)]);
+        assert_eq!(runs(3), vec![(3, 0), (3, 1)]);
+        assert!(ctx.examples().nth(1).unwrap().is_shared_at(1));
+    }
+}
@@ -0,0 +1,119 @@
+//! 有穷例子的后缀树。
+//!
+//! 只使用将来时运算符时，子公式在某个时间点是否成立只取决于从该时间点开始的后缀，因此所有例子中相同的
+//! 后缀只需要一组变量。后缀树的每个节点表示一个后缀，由其第一个时间点的单词与之后的后缀对应的节点
+//! 确定；例子的根节点表示整个序列。
+
+use super::{preprocess::words_at, Context, Example};
+use std::collections::HashMap;
+
+/// 后缀树中的一个节点
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TrieNode {
+    /// 后缀第一个时间点的单词，按序号排列
+    words: Vec<usize>,
+    /// 之后的后缀对应的节点，序列在此结束时为 `None`
+    next: Option<usize>,
+}
+
+impl TrieNode {
+    /// 后缀第一个时间点是否包含单词 `v`
+    pub fn contains(&self, v: usize) -> bool {
+        self.words.binary_search(&v).is_ok()
+    }
+    /// 之后的后缀对应的节点
+    pub fn next(&self) -> Option<usize> {
+        self.next
+    }
+}
+
+/// 所有有穷例子的后缀组成的后缀树，相同的后缀只对应一个节点
+#[derive(Debug, Clone, Default)]
+pub struct SuffixTrie {
+    nodes: Vec<TrieNode>,
+    index: HashMap<(Vec<usize>, Option<usize>), usize>,
+    /// 每个例子的根节点，套索序列为 `None`
+    roots: Vec<Option<usize>>,
+}
+
+impl SuffixTrie {
+    /// 加入有穷例子 `ex` 的所有后缀，返回每个时间点对应的节点
+    pub(super) fn insert(&mut self, ex: &Example) -> Vec<usize> {
+        let mut path = vec![0; ex.size()];
+        let mut next = None;
+        for t in (0..ex.size()).rev() {
+            let words = words_at(ex, t);
+            let nodes = &mut self.nodes;
+            let id = *self.index.entry((words.clone(), next)).or_insert_with(|| {
+                nodes.push(TrieNode { words, next });
+                nodes.len() - 1
+            });
+            path[t] = id;
+            next = Some(id);
+        }
+        path
+    }
+
+    /// 节点数量；若其为 `N`，则节点编号为 `0 <= i < N`
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// 获得编号为 `i` 的节点
+    pub fn node(&self, i: usize) -> &TrieNode {
+        &self.nodes[i]
+    }
+
+    /// 例子 `e` 的根节点，套索序列不在后缀树中
+    pub fn root(&self, e: usize) -> Option<usize> {
+        self.roots.get(e).copied().flatten()
+    }
+}
+
+impl Context {
+    /// 以所有有穷例子建立后缀树，返回节点数量；之后这些例子通过后缀树编码，见
+    /// `enforcers::TrieEnforcer`。过去时运算符与之前的时间点有关，使用时不应调用；
+    /// 应在 `merge_duplicates` 之后调用
+    pub fn build_trie(&mut self) -> usize {
+        let mut trie = SuffixTrie::default();
+        trie.roots = self
+            .examples()
+            .map(|ex| {
+                if ex.lasso().is_some() {
+                    None
+                } else {
+                    Some(trie.insert(ex)[0])
+                }
+            })
+            .collect();
+        let len = trie.len();
+        self.set_trie(trie);
+        len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::{trace, Weight};
+
+    #[test]
+    fn build() {
+        let mut ctx = Context::with_bound(2);
+        ctx.add_example(trace(&[&[0], &[1], &[0, 1]]), true, Weight::default(), None);
+        ctx.add_example(trace(&[&[1], &[1], &[1, 0]]), true, Weight::default(), None);
+        ctx.add_example(trace(&[&[1], &[0, 1]]), false, Weight::default(), None);
+        ctx.add_example(trace(&[&[1], &[0, 1]]), true, Weight::default(), Some(0));
+        // `{0, 1}`、`{1} {0, 1}`、`{0} {1} {0, 1}`、`{1} {1} {0, 1}`，正例与反例共用节点
+        assert_eq!(ctx.build_trie(), 4);
+        let trie = ctx.trie().unwrap();
+        let roots: Vec<_> = (0..4).map(|e| trie.root(e)).collect();
+        assert_eq!(roots, vec![Some(2), Some(3), Some(1), None]);
+        assert_eq!(trie.node(2).next(), Some(1));
+        assert_eq!(trie.node(3).next(), Some(1));
+        assert_eq!(trie.node(1).next(), Some(0));
+        assert_eq!(trie.node(0).next(), None);
+        assert!(trie.node(0).contains(0) && trie.node(0).contains(1));
+        assert!(!trie.node(2).contains(1));
+    }
+}
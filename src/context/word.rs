@@ -1,5 +1,5 @@
 /// 输入样例的一个变量
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Eq, PartialEq, Hash, Clone)]
 pub struct Word(usize);
 
 impl Word {
@@ -7,6 +7,11 @@ impl Word {
         Self(id)
     }
 
+    /// 单词的序号
+    pub fn id(&self) -> usize {
+        self.0
+    }
+
     #[allow(dead_code)]
     pub fn new(context: &super::Context, id: usize) -> Result<Self, ()> {
         if id < context.word_count() {
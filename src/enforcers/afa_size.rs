@@ -1,8 +1,9 @@
-use super::Enforcer;
-use crate::bool_logic::{PropExpr, Variable};
+use super::{Enforcer, RuleSink};
 use crate::context::Context;
 
 /// 保证 AFA 结构能够生成 LTL_f 公式，检查其在 `N - 2` 以后没有二目结构
+///
+/// 规则依赖于尺寸限制，因而全部由 `bound_rules` 生成
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct LTLSizeEnforcer;
 
@@ -14,30 +15,26 @@ impl LTLSizeEnforcer {
 }
 
 impl Enforcer for LTLSizeEnforcer {
-    fn rules(&self, ctx: &Context) -> Vec<PropExpr> {
+    fn generate(&self, _ctx: &Context, _sink: &mut dyn RuleSink) {}
+
+    fn generate_bound(&self, ctx: &Context, sink: &mut dyn RuleSink) {
         let n = ctx.max_skeletons();
-        ((n - 2)..n)
+        let rules = ((n - 2)..n)
             .flat_map(|i| {
-                (&[
-                    Variable::And,
-                    Variable::Or,
-                    Variable::Until,
-                    Variable::Release,
-                ])
-                    .into_iter()
-                    .map(move |f| !f(i.clone()))
+                super::sk_types(ctx)
+                    .map(move |f| f(i))
+                    .filter(|v| v.is_binary())
+                    .map(|v| !v)
             })
             .chain(((n - 1)..n).flat_map(|i| {
-                (&[
-                    Variable::Eventually,
-                    Variable::Next,
-                    Variable::WNext,
-                    Variable::Always,
-                ])
-                    .into_iter()
-                    .map(move |f| !f(i.clone()))
-            }))
-            .collect()
+                super::sk_types(ctx)
+                    .map(move |f| f(i))
+                    .filter(|v| v.is_unary())
+                    .map(|v| !v)
+            }));
+        for rule in rules {
+            sink.add_rule(rule);
+        }
     }
 }
 
@@ -0,0 +1,93 @@
+use super::{Enforcer, RuleSink};
+use crate::bool_logic::Variable;
+use crate::context::Context;
+
+/// 统计不被满足的例子的代价之和
+///
+/// 使用带权重的顺序计数器：`Counter(i, j)` 在例子 `0..=i` 中不被满足的例子的代价之和至少为 `j + 1` 时成立，
+/// 从而求解时假设 `!Counter(m - 1, k)` 即可限制不被满足的例子的代价之和至多为 `k`。
+/// 规则与 Skeleton 无关，只在第一次编码时生成。
+pub struct ErrorBoundEnforcer;
+
+impl Enforcer for ErrorBoundEnforcer {
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        if !ctx.is_noise_tolerant() || !ctx.is_fresh(0) {
+            return;
+        }
+        // 例子 `0..i` 的代价之和
+        let mut prefix = 0;
+        for e in ctx.examples() {
+            let i = e.id();
+            let w = e.weight().cost();
+            if i > 0 {
+                for j in 0..prefix {
+                    sink.add_rule(Variable::Counter(i - 1, j) >> Variable::Counter(i, j));
+                    if w > 0 {
+                        sink.add_rule(
+                            (Variable::Relax(i) & Variable::Counter(i - 1, j))
+                                >> Variable::Counter(i, j + w),
+                        );
+                    }
+                }
+            }
+            for j in 0..w {
+                sink.add_rule(Variable::Relax(i) >> Variable::Counter(i, j));
+            }
+            prefix += w;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bool_logic::PropExpr;
+    use crate::context::{Weight, Word};
+    use std::collections::HashSet;
+
+    #[test]
+    fn weighted() {
+        let mut ctx = Context::with_bound(2);
+        for w in [Weight::Soft(2), Weight::Hard, Weight::Soft(3)].iter() {
+            ctx.add_example(vec![HashSet::<Word>::new()], true, *w, None);
+        }
+        ctx.set_max_errors(Some(1));
+        let rules = PropExpr::chained_and(ErrorBoundEnforcer.rules(&ctx));
+        // 例子 0、2 不被满足，代价之和为 5
+        let mut model = vec![Variable::Relax(0), Variable::Relax(2)];
+        for i in 0..3 {
+            for j in 0..2 {
+                model.push(Variable::Counter(i, j));
+            }
+        }
+        for j in 2..5 {
+            model.push(Variable::Counter(2, j));
+        }
+        assert!(rules._validate(&model));
+        model.pop();
+        assert!(!rules._validate(&model));
+    }
+
+    #[test]
+    fn count() {
+        let mut ctx = Context::with_bound(2);
+        for _ in 0..4 {
+            ctx.add_example(vec![HashSet::<Word>::new()], true, Weight::default(), None);
+        }
+        ctx.set_max_errors(Some(1));
+        let rules = PropExpr::chained_and(ErrorBoundEnforcer.rules(&ctx));
+        // 例子 1、3 不被满足
+        let mut model = vec![
+            Variable::Relax(1),
+            Variable::Relax(3),
+            Variable::Counter(1, 0),
+            Variable::Counter(2, 0),
+            Variable::Counter(3, 0),
+            Variable::Counter(3, 1),
+        ];
+        assert!(rules._validate(&model));
+        // 至少两个例子不被满足，计数器不能少计
+        model.pop();
+        assert!(!rules._validate(&model));
+    }
+}
@@ -1,9 +1,9 @@
-use super::Enforcer;
+use super::{Enforcer, RuleSink};
 use crate::bool_logic::{PropExpr, Variable};
-use crate::context::{Context, Example};
+use crate::context::{Context, Example, Weight};
 
-fn get_maybe_dual(v: Variable, e: &Example) -> Variable {
-    if e.is_pos() {
+fn get_maybe_dual(v: Variable, pos: bool) -> Variable {
+    if pos {
         v
     } else {
         match v {
@@ -15,100 +15,181 @@ fn get_maybe_dual(v: Variable, e: &Example) -> Variable {
             Variable::Release(s) => Variable::Until(s),
             Variable::Eventually(s) => Variable::Always(s),
             Variable::Always(s) => Variable::Eventually(s),
+            Variable::Yesterday(s) => Variable::WYesterday(s),
+            Variable::WYesterday(s) => Variable::Yesterday(s),
+            Variable::Since(s) => Variable::Trigger(s),
+            Variable::Trigger(s) => Variable::Since(s),
+            Variable::Once(s) => Variable::Historically(s),
+            Variable::Historically(s) => Variable::Once(s),
+            Variable::True(s) => Variable::False(s),
+            Variable::False(s) => Variable::True(s),
+            Variable::Not(s) => Variable::Not(s),
+            Variable::Implies(s) => Variable::Implies(s),
             Variable::Literal(s) => Variable::Literal(s),
             _ => unreachable!(),
         }
     }
 }
 
+/// `flipped` 为真时以相反的极性编码例子，使用 `FlipRun` 代替 `Run`
 fn make_rule(
     ctx: &Context,
     ex: &Example,
+    flipped: bool,
     ty: Variable,
     s1: usize,
     s2: usize,
     t: usize,
 ) -> Vec<PropExpr> {
-    let e = ex.id();
-    let e_max_t = ex.size() - 1;
+    // 后继时间点，套索序列的最后一个时间点回到循环起点
+    let next = ex.next(t);
     use Variable::*;
-    match ty {
-        Variable::And(s) => {
-            if t == e_max_t {
-                vec![]
-            } else {
-                vec![
-                    Run(e, t, s1) << (Run(e, t, s) & get_maybe_dual(And(s), ex) & LeftChild(s, s1)),
-                    Run(e, t, s2)
-                        << (Run(e, t, s) & get_maybe_dual(And(s), ex) & RightChild(s, s2)),
-                ]
-            }
+    let pos = ex.is_pos() ^ flipped;
+    // 后缀与其它例子相同的时间点使用其它例子的变量
+    let run = |t, s| {
+        let (e, t) = ex.run_at(t);
+        if flipped {
+            FlipRun(e, t, s)
+        } else {
+            Run(e, t, s)
         }
+    };
+    let flip = |t, s| {
+        let (e, t) = ex.run_at(t);
+        if flipped {
+            Run(e, t, s)
+        } else {
+            FlipRun(e, t, s)
+        }
+    };
+    match ty {
+        Variable::And(s) => vec![
+            run(t, s1) << (run(t, s) & get_maybe_dual(And(s), pos) & LeftChild(s, s1)),
+            run(t, s2) << (run(t, s) & get_maybe_dual(And(s), pos) & RightChild(s, s2)),
+        ],
         Variable::Or(s) => vec![
-            (Run(e, t, s1) | Run(e, t, s2))
-                << (Run(e, t, s)
-                    & get_maybe_dual(Or(s), ex)
-                    & LeftChild(s, s1)
-                    & RightChild(s, s2)),
+            (run(t, s1) | run(t, s2))
+                << (run(t, s) & get_maybe_dual(Or(s), pos) & LeftChild(s, s1) & RightChild(s, s2)),
         ],
         Variable::Next(s) => vec![
-            (if t < e_max_t {
-                Run(e, t + 1, s1)
+            (if let Some(t1) = next {
+                run(t1, s1)
             } else {
                 Exactly(false)
-            }) << (Run(e, t, s) & get_maybe_dual(Next(s), ex) & LeftChild(s, s1)),
+            }) << (run(t, s) & get_maybe_dual(Next(s), pos) & LeftChild(s, s1)),
         ],
         Variable::WNext(s) => vec![
-            (if t < e_max_t {
-                Run(e, t + 1, s1)
+            (if let Some(t1) = next {
+                run(t1, s1)
             } else {
                 Exactly(true)
-            }) << (Run(e, t, s) & get_maybe_dual(WNext(s), ex) & LeftChild(s, s1)),
+            }) << (run(t, s) & get_maybe_dual(WNext(s), pos) & LeftChild(s, s1)),
         ],
-        Variable::Until(s) => vec![if t < e_max_t {
-            (Run(e, t, s2) | (Run(e, t + 1, s) & Run(e, t, s1)))
-                << (Run(e, t, s)
-                    & get_maybe_dual(Until(s), ex)
+        Variable::Until(s) => std::iter::once(Some(if let Some(t1) = next {
+            (run(t, s2) | (run(t1, s) & run(t, s1)))
+                << (run(t, s)
+                    & get_maybe_dual(Until(s), pos)
                     & LeftChild(s, s1)
                     & RightChild(s, s2))
         } else {
-            Run(e, t, s2) << (Run(e, t, s) & get_maybe_dual(Until(s), ex) & RightChild(s, s2))
-        }],
+            run(t, s2) << (run(t, s) & get_maybe_dual(Until(s), pos) & RightChild(s, s2))
+        }))
+        // 套索序列上上述规则允许无限推迟，需要额外要求 `b` 最终成立
+        .chain(std::iter::once(if ex.lasso().is_some() {
+            Some(
+                PropExpr::chained_or(ex.reach(t).map(|k| run(k, s2).into()).collect())
+                    << (run(t, s) & get_maybe_dual(Until(s), pos) & RightChild(s, s2)),
+            )
+        } else {
+            None
+        }))
+        .flatten()
+        .collect(),
         Variable::Release(s) => std::iter::once(Some(
-            Run(e, t, s2) << (Run(e, t, s) & get_maybe_dual(Release(s), ex) & RightChild(s, s2)),
+            run(t, s2) << (run(t, s) & get_maybe_dual(Release(s), pos) & RightChild(s, s2)),
+        ))
+        .chain(std::iter::once(next.map(|t1| {
+            (run(t, s1) | run(t1, s))
+                << (run(t, s)
+                    & get_maybe_dual(Release(s), pos)
+                    & LeftChild(s, s1)
+                    & RightChild(s, s2))
+        })))
+        .flatten()
+        .collect(),
+        Variable::Eventually(s) => vec![
+            (if ex.lasso().is_some() {
+                // 套索序列上不能无限推迟，直接要求某个可达的时间点满足
+                PropExpr::chained_or(ex.reach(t).map(|k| run(k, s1).into()).collect())
+            } else if let Some(t1) = next {
+                run(t, s1) | run(t1, s)
+            } else {
+                run(t, s1).into()
+            }) << (run(t, s) & get_maybe_dual(Eventually(s), pos) & LeftChild(s, s1)),
+        ],
+        Variable::Always(s) => std::iter::once(Some(
+            run(t, s1) << (run(t, s) & get_maybe_dual(Always(s), pos) & LeftChild(s, s1)),
         ))
-        .chain(std::iter::once(if t < e_max_t {
+        .chain(std::iter::once(next.map(|t1| {
+            run(t1, s) << (run(t, s) & get_maybe_dual(Always(s), pos) & LeftChild(s, s1))
+        })))
+        .flatten()
+        .collect(),
+        Variable::Yesterday(s) => vec![
+            (if t > 0 {
+                run(t - 1, s1)
+            } else {
+                Exactly(false)
+            }) << (run(t, s) & get_maybe_dual(Yesterday(s), pos) & LeftChild(s, s1)),
+        ],
+        Variable::WYesterday(s) => vec![
+            (if t > 0 { run(t - 1, s1) } else { Exactly(true) })
+                << (run(t, s) & get_maybe_dual(WYesterday(s), pos) & LeftChild(s, s1)),
+        ],
+        Variable::Since(s) => vec![if t > 0 {
+            (run(t, s2) | (run(t - 1, s) & run(t, s1)))
+                << (run(t, s)
+                    & get_maybe_dual(Since(s), pos)
+                    & LeftChild(s, s1)
+                    & RightChild(s, s2))
+        } else {
+            run(t, s2) << (run(t, s) & get_maybe_dual(Since(s), pos) & RightChild(s, s2))
+        }],
+        Variable::Trigger(s) => std::iter::once(Some(
+            run(t, s2) << (run(t, s) & get_maybe_dual(Trigger(s), pos) & RightChild(s, s2)),
+        ))
+        .chain(std::iter::once(if t > 0 {
             Some(
-                (Run(e, t, s1) | Run(e, t + 1, s))
-                    << (Run(e, t, s)
-                        & get_maybe_dual(Release(s), ex)
+                (run(t, s1) | run(t - 1, s))
+                    << (run(t, s)
+                        & get_maybe_dual(Trigger(s), pos)
                         & LeftChild(s, s1)
                         & RightChild(s, s2)),
             )
         } else {
             None
         }))
-        .filter_map(|x| x)
+        .flatten()
         .collect(),
-        Variable::Eventually(s) => vec![
-            (if t < e_max_t {
-                Run(e, t, s1) | Run(e, t + 1, s)
+        Variable::Once(s) => vec![
+            (if t > 0 {
+                run(t, s1) | run(t - 1, s)
             } else {
-                Run(e, t, s1).into()
-            }) << (Run(e, t, s) & get_maybe_dual(Eventually(s), ex) & LeftChild(s, s1)),
+                run(t, s1).into()
+            }) << (run(t, s) & get_maybe_dual(Once(s), pos) & LeftChild(s, s1)),
         ],
-        Variable::Always(s) => std::iter::once(Some(
-            Run(e, t, s1) << (Run(e, t, s) & get_maybe_dual(Always(s), ex) & LeftChild(s, s1)),
+        Variable::Historically(s) => std::iter::once(Some(
+            run(t, s1) << (run(t, s) & get_maybe_dual(Historically(s), pos) & LeftChild(s, s1)),
         ))
-        .chain(std::iter::once(if t < e_max_t {
+        .chain(std::iter::once(if t > 0 {
             Some(
-                Run(e, t + 1, s)
-                    << (Run(e, t, s) & get_maybe_dual(Always(s), ex) & LeftChild(s, s1)),
+                run(t - 1, s)
+                    << (run(t, s) & get_maybe_dual(Historically(s), pos) & LeftChild(s, s1)),
             )
         } else {
             None
         }))
-        .filter_map(|x| x)
+        .flatten()
         .collect(),
         Variable::Literal(s) => (0..ctx.word_count())
             .map(|v| {
@@ -117,56 +198,103 @@ fn make_rule(
                 // 0 1   1
                 // 1 0   1
                 // 1 1   0
-                if ex.is_pos() ^ ex.contains_at(t, v) {
-                    Exactly(false) << (Run(e, t, s) & Literal(s) & Word(s, v, true))
+                if pos ^ ex.contains_at(t, v) {
+                    Exactly(false) << (run(t, s) & Literal(s) & Word(s, v, true))
                 } else {
-                    Exactly(false) << (Run(e, t, s) & Literal(s) & Word(s, v, false))
+                    Exactly(false) << (run(t, s) & Literal(s) & Word(s, v, false))
                 }
             })
             .collect(),
+        Variable::Not(s) => {
+            vec![flip(t, s1) << (run(t, s) & get_maybe_dual(Not(s), pos) & LeftChild(s, s1))]
+        }
+        // `a -> b` 的否定为 `a & !b`，没有对应的节点类型，因此按极性分别编码
+        Variable::Implies(s) => vec![
+            (if pos {
+                flip(t, s1) | run(t, s2)
+            } else {
+                flip(t, s1) & run(t, s2)
+            }) << (run(t, s)
+                & get_maybe_dual(Implies(s), pos)
+                & LeftChild(s, s1)
+                & RightChild(s, s2)),
+        ],
+        Variable::True(_) => vec![],
+        Variable::False(s) => {
+            vec![Exactly(false) << (run(t, s) & get_maybe_dual(False(s), pos))]
+        }
         _ => unreachable!(), // 无其它规则
     }
 }
 
-/// 确保给定类型的子树的正例规则
+/// 确保给定类型的节点在例子上成立的规则，反例通过对偶运算符的规则编码
 #[derive(Debug, Copy, Clone)]
-pub struct ExampleEnforcer<'a>(Variable, &'a Example);
+pub struct ExampleEnforcer<'a>(Variable, &'a Example, bool);
 
 impl<'a> ExampleEnforcer<'a> {
-    /// 构造正例的 Enforcer
+    /// 构造节点类型 `ty` 在例子 `ex` 上的 Enforcer
     pub fn new(ty: Variable, ex: &'a Example) -> Self {
-        Self(ty, ex)
+        Self(ty, ex, false)
+    }
+
+    /// 以相反的极性编码例子 `ex`，仅当 `!` 或 `->` 需要子树在例子上不成立时使用，
+    /// 因此不要求根节点被满足
+    pub fn flipped(ty: Variable, ex: &'a Example) -> Self {
+        Self(ty, ex, true)
     }
 }
 
 impl<'a> Enforcer for ExampleEnforcer<'a> {
-    fn rules(&self, ctx: &Context) -> Vec<PropExpr> {
-        let mut ret = vec![];
-        ret.append(&mut vec![Variable::Run(self.1.id(), 0, 0).into()]);
-        if self.0.is_atom() {
-            for t in 0..(self.1.size()) {
-                ret.append(&mut make_rule(
-                    ctx, self.1, self.0, 0x7F7F7F7F, 0x7F7F7F7F, t,
-                ));
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        if !self.2 && ctx.is_fresh(self.0.skeleton_id()) {
+            let e = self.1.id();
+            let (root, t) = self.1.run_at(0);
+            // 必须被满足的例子不能放弃
+            sink.add_rule(
+                if ctx.is_noise_tolerant() && self.1.weight() != Weight::Hard {
+                    Variable::Run(root, t, 0) | Variable::Relax(e)
+                } else {
+                    Variable::Run(root, t, 0).into()
+                },
+            );
+        }
+        // 使用其它例子的变量的时间点，其规则由其它例子生成
+        let times = || (0..self.1.size()).filter(|t| !self.1.is_shared_at(*t));
+        // 反例使用对偶运算符的规则，其条件为节点类型本身
+        let ty = get_maybe_dual(self.0, self.1.is_pos() ^ self.2);
+        if self.0.is_atom() && ctx.is_fresh(self.0.skeleton_id()) {
+            for t in times() {
+                for rule in make_rule(ctx, self.1, self.2, ty, 0x7F7F7F7F, 0x7F7F7F7F, t) {
+                    sink.add_rule(rule);
+                }
             }
         }
         if self.0.is_unary() {
             for s1 in (self.0.skeleton_id() + 1)..ctx.max_skeletons() {
-                for t in 0..(self.1.size()) {
-                    ret.append(&mut make_rule(ctx, self.1, self.0, s1, 0x7F7F7F7F, t));
+                if !ctx.is_fresh(s1) {
+                    continue;
+                }
+                for t in times() {
+                    for rule in make_rule(ctx, self.1, self.2, ty, s1, 0x7F7F7F7F, t) {
+                        sink.add_rule(rule);
+                    }
                 }
             }
         }
         if self.0.is_binary() {
             for s1 in (self.0.skeleton_id() + 1)..ctx.max_skeletons() {
                 for s2 in (self.0.skeleton_id() + 2)..ctx.max_skeletons() {
-                    for t in 0..(self.1.size()) {
-                        ret.append(&mut make_rule(ctx, self.1, self.0, s1, s2, t));
+                    if !ctx.is_fresh(s1.max(s2)) {
+                        continue;
+                    }
+                    for t in times() {
+                        for rule in make_rule(ctx, self.1, self.2, ty, s1, s2, t) {
+                            sink.add_rule(rule);
+                        }
                     }
                 }
             }
         }
-        ret
     }
 }
 
@@ -1,5 +1,5 @@
-use super::Enforcer;
-use crate::bool_logic::{PropExpr, Variable};
+use super::{Enforcer, RuleSink};
+use crate::bool_logic::Variable;
 use crate::context::Context;
 
 /// 保证 AFA 结构能够生成 LTL_f 公式，检查其存在符合要求的子树
@@ -20,50 +20,33 @@ impl LTLSubtreeEnforcer {
 }
 
 impl Enforcer for LTLSubtreeEnforcer {
-    fn rules(&self, ctx: &Context) -> Vec<PropExpr> {
-        let mut rules = Vec::new();
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
         let ty = &self.0;
         let n = ctx.max_skeletons();
         let word_cnt = ctx.word_count();
-        // has left subtree
-        if ty.is_unary() {
-            rules.push(
-                self.0
-                    >> super::one_of(
-                        ((self.0.skeleton_id() + 1)..n)
-                            .map(|s1| Variable::LeftChild(self.0.skeleton_id(), s1)),
-                    ),
-            );
+        let i = self.0.skeleton_id();
+        let child_id = |v: &Variable| match v {
+            Variable::LeftChild(_, s) | Variable::RightChild(_, s) => *s,
+            _ => unreachable!(),
+        };
+        let mut at_most_one = |vars: Vec<Variable>| {
+            for rule in super::at_most_one(&vars, |_, v| ctx.is_fresh(child_id(v))) {
+                sink.add_rule(self.0 >> rule);
+            }
+        };
+        // has at most one left subtree
+        if ty.is_unary() || ty.is_binary() {
+            at_most_one(((i + 1)..n).map(|s1| Variable::LeftChild(i, s1)).collect());
         }
-        // has right subtree
+        // has at most one right subtree
         if ty.is_binary() {
-            rules.push(
-                self.0
-                    >> super::one_of(
-                        ((self.0.skeleton_id() + 1)..(n - 1))
-                            .map(|s1| Variable::LeftChild(self.0.skeleton_id(), s1)),
-                    ),
-            );
-            rules.push(
-                self.0
-                    >> super::one_of(
-                        ((self.0.skeleton_id() + 2)..n)
-                            .map(|s2| Variable::RightChild(self.0.skeleton_id(), s2)),
-                    ),
-            );
+            at_most_one(((i + 2)..n).map(|s2| Variable::RightChild(i, s2)).collect());
         }
         // is literal
-        if ty.is_atom() {
-            debug_assert!(if let Variable::Literal(_) = self.0 {
-                true
-            } else {
-                false
-            });
-
+        if matches!(self.0, Variable::Literal(_)) && ctx.is_fresh(i) {
             // TODO: add negative literal word
-            rules.push({
+            sink.add_rule({
                 let range = 0..word_cnt;
-                let i = self.0.skeleton_id();
                 use itertools::Itertools;
 
                 self.0
@@ -75,21 +58,77 @@ impl Enforcer for LTLSubtreeEnforcer {
                     )
             });
         }
-        rules
+    }
+
+    fn generate_bound(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        let ty = &self.0;
+        let n = ctx.max_skeletons();
+        let i = self.0.skeleton_id();
+        // has left subtree
+        if ty.is_unary() {
+            sink.add_rule(
+                self.0 >> super::at_least_one(((i + 1)..n).map(|s1| Variable::LeftChild(i, s1))),
+            );
+        }
+        // has right subtree
+        if ty.is_binary() {
+            sink.add_rule(
+                self.0
+                    >> super::at_least_one(((i + 1)..(n - 1)).map(|s1| Variable::LeftChild(i, s1))),
+            );
+            sink.add_rule(
+                self.0 >> super::at_least_one(((i + 2)..n).map(|s2| Variable::RightChild(i, s2))),
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::bool_logic::PropExpr;
 
     #[test]
     fn has_left_subtree() {
+        let ctx = Context::with_bound(4);
+        let enforcer = LTLSubtreeEnforcer::new(Variable::Eventually(2));
+        let mut rules = enforcer.rules(&ctx);
+        rules.append(&mut enforcer.bound_rules(&ctx));
+        let rules = PropExpr::chained_and(rules);
+        assert!(!rules._validate(&[Variable::Eventually(2)]));
+        assert!(!rules._validate(&[Variable::Eventually(2), Variable::LeftChild(2, 1)]));
+        assert!(rules._validate(&[Variable::Eventually(2), Variable::LeftChild(2, 3)]));
+    }
+
+    #[test]
+    fn single_left_subtree() {
         let ctx = Context::with_bound(4);
         let rules =
-            PropExpr::chained_and(LTLSubtreeEnforcer::new(Variable::Eventually(2)).rules(&ctx));
-        assert!(!rules._validate(&vec![Variable::Eventually(2)]));
-        assert!(!rules._validate(&vec![Variable::Eventually(2), Variable::LeftChild(2, 1)]));
-        assert!(rules._validate(&vec![Variable::Eventually(2), Variable::LeftChild(2, 3)]));
+            PropExpr::chained_and(LTLSubtreeEnforcer::new(Variable::Eventually(1)).rules(&ctx));
+        assert!(!rules._validate(&[
+            Variable::Eventually(1),
+            Variable::LeftChild(1, 2),
+            Variable::LeftChild(1, 3)
+        ]));
+    }
+
+    #[test]
+    fn incremental() {
+        let mut ctx = Context::with_bound(4);
+        ctx.set_encoded_skeletons(3);
+        let rules = LTLSubtreeEnforcer::new(Variable::Eventually(0)).rules(&ctx);
+        // 只生成涉及新节点 3 的约束
+        assert_eq!(rules.len(), 2);
+        let rules = PropExpr::chained_and(rules);
+        assert!(rules._validate(&[
+            Variable::Eventually(0),
+            Variable::LeftChild(0, 1),
+            Variable::LeftChild(0, 2)
+        ]));
+        assert!(!rules._validate(&[
+            Variable::Eventually(0),
+            Variable::LeftChild(0, 1),
+            Variable::LeftChild(0, 3)
+        ]));
     }
 }
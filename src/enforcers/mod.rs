@@ -8,29 +8,81 @@
 //! 4. `LTLSubtreeEnforcer` 保证每个节点存在编号大于其自身的子树。
 //! 5. `LTLSizeEnforcer` 检查在 `N-2`（`N-1`）以后不存在二目（单目）子树，从而确保尺寸限制
 //! 6. `ExampleEnforcer` 确保生成的结构接受正例（反例）
+//! 7. `ErrorBoundEnforcer` 在允许例子不被满足时，统计不被满足的例子数量
+//! 8. `SymmetryEnforcer` 要求节点按照广度优先的顺序编号，消除同一公式的不同编号
+//! 9. `TrieEnforcer` 与 `TrieRootEnforcer` 在建立后缀树（见 `Context::build_trie`）时代替
+//!    `ExampleEnforcer` 编码有穷例子，每个后缀只编码一次，正例与反例共用
+//!
+//! 为了支持在不同尺寸限制之间增量求解，规则分为两类：`Enforcer::rules` 生成的规则在尺寸限制增大后
+//! 依然成立，并且只包含涉及尚未编码的 Skeleton（见 `Context::is_fresh`）的规则；
+//! `Enforcer::bound_rules` 生成的规则只在当前尺寸限制下成立，求解时需要由 `Variable::Bound` 激活。
+//!
+//! 规则数量与例子数量、长度以及尺寸限制的乘积成正比，因此规则通过 `Enforcer::generate` 逐条交给
+//! `RuleSink`，由其直接转换并加入求解器，而不是先收集所有规则。
 
 use crate::{
     bool_logic::{BinaryOp, PropExpr, Variable},
-    context::Context,
+    context::{Context, Example},
+    ltl::Operator,
 };
 
 mod afa_size;
+mod error_bound;
 mod example;
 mod ltl_afa;
 mod size_bound;
 mod structure;
+mod symmetry;
+mod trie;
 
 pub use afa_size::LTLSizeEnforcer;
+pub use error_bound::ErrorBoundEnforcer;
 pub use example::ExampleEnforcer;
 pub use ltl_afa::LTLSubtreeEnforcer;
 pub use size_bound::SizeBoundEnforcer;
 pub use structure::AFASkTypeEnforcer;
 pub use structure::AFASpecificStructureEnforcer;
+pub use symmetry::SymmetryEnforcer;
+pub use trie::{TrieEnforcer, TrieRootEnforcer};
+
+/// 接收生成的规则
+pub trait RuleSink {
+    /// 接收一条规则
+    fn add_rule(&mut self, rule: PropExpr);
+
+    /// 之后的规则由名为 `name` 的规则生成器生成，用于按规则生成器统计
+    fn enforcer(&mut self, _name: &'static str) {}
+}
+
+impl RuleSink for Vec<PropExpr> {
+    fn add_rule(&mut self, rule: PropExpr) {
+        self.push(rule);
+    }
+}
 
 pub trait Enforcer {
+    /// 生成规则，逐条交给 `sink`
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink);
+
+    /// 生成只在当前尺寸限制下成立的规则，逐条交给 `sink`
+    fn generate_bound(&self, _ctx: &Context, _sink: &mut dyn RuleSink) {}
+
     /// 生成规则
-    fn rules(&self, ctx: &Context) -> Vec<PropExpr>;
+    fn rules(&self, ctx: &Context) -> Vec<PropExpr> {
+        let mut rules = vec![];
+        self.generate(ctx, &mut rules);
+        rules
+    }
+
+    /// 生成只在当前尺寸限制下成立的规则
+    #[allow(dead_code)]
+    fn bound_rules(&self, ctx: &Context) -> Vec<PropExpr> {
+        let mut rules = vec![];
+        self.generate_bound(ctx, &mut rules);
+        rules
+    }
 
+    #[allow(dead_code)]
     fn rules_cnf(&self, ctx: &Context) -> Vec<PropExpr> {
         self.rules(ctx)
             .into_iter()
@@ -49,7 +101,7 @@ fn one_of(iter: impl Iterator<Item = Variable> + Clone) -> PropExpr {
         // create a chained_or where only u is not neg
         sub_routes.push(PropExpr::chained_and(
             iter.clone()
-                .map(|v| if v == u { v.clone().into() } else { !v.clone() })
+                .map(|v| if v == u { v.into() } else { !v })
                 .collect(),
         ));
     }
@@ -57,55 +109,149 @@ fn one_of(iter: impl Iterator<Item = Variable> + Clone) -> PropExpr {
     PropExpr::chained_or(sub_routes)
 }
 
-pub struct ContextEnforcer;
+/// 两两不同时成立，从而至多有一个变量为真；`is_fresh` 判断一对变量的约束是否需要生成
+fn at_most_one(
+    vars: &[Variable],
+    is_fresh: impl Fn(&Variable, &Variable) -> bool,
+) -> Vec<PropExpr> {
+    let mut ret = vec![];
+    for (i, u) in vars.iter().enumerate() {
+        for v in vars.iter().skip(i + 1) {
+            if is_fresh(u, v) {
+                ret.push(!u.to_owned() | !v.to_owned());
+            }
+        }
+    }
+    ret
+}
 
-impl Enforcer for ContextEnforcer {
-    fn rules(&self, ctx: &Context) -> Vec<PropExpr> {
-        const SK_TYPES: &[fn(usize) -> Variable] = &[
-            Variable::Literal,
-            Variable::And,
-            Variable::Or,
-            Variable::Until,
-            Variable::Release,
-            Variable::Eventually,
-            Variable::Next,
-            Variable::WNext,
-            Variable::Always,
-        ];
+/// 至少有一个变量为真
+fn at_least_one(iter: impl Iterator<Item = Variable>) -> PropExpr {
+    PropExpr::chained_or(iter.map(PropExpr::var).collect())
+}
+
+/// 运算符对应的节点类型
+fn sk_type(op: Operator) -> fn(usize) -> Variable {
+    match op {
+        Operator::And => Variable::And,
+        Operator::Or => Variable::Or,
+        Operator::Until => Variable::Until,
+        Operator::Release => Variable::Release,
+        Operator::Eventually => Variable::Eventually,
+        Operator::Next => Variable::Next,
+        Operator::WNext => Variable::WNext,
+        Operator::Always => Variable::Always,
+        Operator::Since => Variable::Since,
+        Operator::Trigger => Variable::Trigger,
+        Operator::Once => Variable::Once,
+        Operator::Historically => Variable::Historically,
+        Operator::Yesterday => Variable::Yesterday,
+        Operator::WYesterday => Variable::WYesterday,
+        Operator::Not => Variable::Not,
+        Operator::Implies => Variable::Implies,
+        Operator::True => Variable::True,
+        Operator::False => Variable::False,
+    }
+}
 
+/// 编码中使用的节点类型，字面量总是可用，其余由 `Context::operators` 决定
+fn sk_types(ctx: &Context) -> impl Iterator<Item = fn(usize) -> Variable> + '_ {
+    std::iter::once(Variable::Literal as fn(usize) -> Variable)
+        .chain(ctx.operators().iter().map(|op| sk_type(*op)))
+}
+
+pub struct ContextEnforcer;
+
+impl ContextEnforcer {
+    /// 生成与例子无关的 AFA 结构规则
+    pub fn generate_structure(&self, ctx: &Context, sink: &mut dyn RuleSink) {
         let n = ctx.max_skeletons();
-        let mut ret = vec![];
-        // AFASkTypeEnforcer
+        sink.enforcer("AFASkTypeEnforcer");
         for i in 0..n {
-            ret.append(&mut AFASkTypeEnforcer::new(i).rules(ctx));
+            AFASkTypeEnforcer::new(i).generate(ctx, sink);
         }
-        // AFASpecificStructureEnforcer
+        sink.enforcer("AFASpecificStructureEnforcer");
         for i in 0..n {
-            for ty in SK_TYPES {
-                ret.append(&mut AFASpecificStructureEnforcer::new(ty(i)).rules(ctx));
+            for ty in sk_types(ctx) {
+                AFASpecificStructureEnforcer::new(ty(i)).generate(ctx, sink);
             }
         }
-        // SizeBoundEnforcer
+        sink.enforcer("SizeBoundEnforcer");
         for i in 0..n {
-            ret.append(&mut SizeBoundEnforcer::new(i).rules(ctx));
+            SizeBoundEnforcer::new(i).generate(ctx, sink);
         }
-        // LTLSubtreeEnforcer
+        sink.enforcer("LTLSubtreeEnforcer");
         for i in 0..n {
-            for ty in SK_TYPES {
-                ret.append(&mut LTLSubtreeEnforcer::new(ty(i)).rules(ctx));
+            for ty in sk_types(ctx) {
+                LTLSubtreeEnforcer::new(ty(i)).generate(ctx, sink);
             }
         }
-        // LTLSizeEnforcer
-        ret.append(&mut LTLSizeEnforcer::new().rules(ctx));
-        // ExampleEnforcer
-        for e in ctx.examples() {
-            for i in 0..n {
-                for ty in SK_TYPES {
-                    ret.append(&mut ExampleEnforcer::new(ty(i), e).rules(ctx));
+    }
+
+    /// 生成例子 `e` 的规则；不同例子的规则互不相关，可以分别生成后按例子的顺序合并。
+    /// 例子在后缀树中时只生成其根节点的规则，其余规则由 `generate_trie_node` 生成
+    pub fn generate_example(&self, ctx: &Context, e: &Example, sink: &mut dyn RuleSink) {
+        if ctx.trie().and_then(|trie| trie.root(e.id())).is_some() {
+            sink.enforcer("TrieEnforcer");
+            TrieRootEnforcer::new(e).generate(ctx, sink);
+            return;
+        }
+        // `!` 与 `->` 还需要以相反的极性编码例子
+        let has_negation = ctx
+            .operators()
+            .iter()
+            .any(|op| matches!(op, Operator::Not | Operator::Implies));
+        sink.enforcer("ExampleEnforcer");
+        for i in 0..ctx.max_skeletons() {
+            for ty in sk_types(ctx) {
+                ExampleEnforcer::new(ty(i), e).generate(ctx, sink);
+                if has_negation {
+                    ExampleEnforcer::flipped(ty(i), e).generate(ctx, sink);
                 }
             }
         }
+    }
+
+    /// 生成后缀树节点 `n` 的规则；与例子的规则一样可以分别生成后按节点的顺序合并
+    pub fn generate_trie_node(&self, ctx: &Context, n: usize, sink: &mut dyn RuleSink) {
+        sink.enforcer("TrieEnforcer");
+        for i in 0..ctx.max_skeletons() {
+            for ty in sk_types(ctx) {
+                TrieEnforcer::new(ty(i), n).generate(ctx, sink);
+            }
+        }
+    }
 
-        ret
+    /// 生成涉及所有例子或所有节点的规则
+    pub fn generate_global(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        sink.enforcer("ErrorBoundEnforcer");
+        ErrorBoundEnforcer.generate(ctx, sink);
+        sink.enforcer("SymmetryEnforcer");
+        SymmetryEnforcer.generate(ctx, sink);
+    }
+}
+
+impl Enforcer for ContextEnforcer {
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        self.generate_structure(ctx, sink);
+        for e in ctx.examples() {
+            self.generate_example(ctx, e, sink);
+        }
+        for n in 0..ctx.trie().map_or(0, |trie| trie.len()) {
+            self.generate_trie_node(ctx, n, sink);
+        }
+        self.generate_global(ctx, sink);
+    }
+
+    fn generate_bound(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        let n = ctx.max_skeletons();
+        sink.enforcer("LTLSubtreeEnforcer");
+        for i in 0..n {
+            for ty in sk_types(ctx) {
+                LTLSubtreeEnforcer::new(ty(i)).generate_bound(ctx, sink);
+            }
+        }
+        sink.enforcer("LTLSizeEnforcer");
+        LTLSizeEnforcer::new().generate_bound(ctx, sink);
     }
 }
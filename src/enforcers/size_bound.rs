@@ -1,4 +1,4 @@
-use super::Enforcer;
+use super::{Enforcer, RuleSink};
 use crate::bool_logic::{PropExpr, Variable};
 use crate::context::Context;
 
@@ -12,30 +12,31 @@ impl SizeBoundEnforcer {
 }
 
 impl Enforcer for SizeBoundEnforcer {
-    fn rules(&self, ctx: &Context) -> Vec<PropExpr> {
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
         let n = ctx.max_skeletons();
-        let mut ret = vec![];
         for i in 0..n {
             for j in 0..n {
+                if !ctx.is_fresh(i.max(j).max(self.0)) {
+                    continue;
+                }
                 if i != j && i != self.0 && j != self.0 {
-                    ret.push(PropExpr::or(
+                    sink.add_rule(PropExpr::or(
                         !Variable::LeftChild(i, self.0),
                         !Variable::LeftChild(j, self.0),
                     ));
-                    ret.push(PropExpr::or(
+                    sink.add_rule(PropExpr::or(
                         !Variable::RightChild(i, self.0),
                         !Variable::RightChild(j, self.0),
                     ));
                 }
                 if i != self.0 && j != self.0 {
-                    ret.push(PropExpr::or(
+                    sink.add_rule(PropExpr::or(
                         !Variable::LeftChild(i, self.0),
                         !Variable::RightChild(j, self.0),
                     ));
                 }
             }
         }
-        ret
     }
 }
 
@@ -48,15 +49,12 @@ mod test {
         let ctx = Context::with_bound(4);
         let rules = PropExpr::chained_and(SizeBoundEnforcer::new(2).rules(&ctx));
         println!("{:?}", rules);
-        assert!(!rules._validate(&vec![Variable::LeftChild(1, 2), Variable::LeftChild(3, 2)]));
-        assert!(!rules._validate(&vec![Variable::LeftChild(1, 2), Variable::RightChild(1, 2)]));
-        assert!(!rules._validate(&vec![Variable::LeftChild(1, 2), Variable::RightChild(3, 2)]));
-        assert!(!rules._validate(&vec![
-            Variable::RightChild(1, 2),
-            Variable::RightChild(3, 2)
-        ]));
-        assert!(rules._validate(&vec![Variable::LeftChild(1, 3), Variable::LeftChild(2, 3)]));
-        assert!(rules._validate(&vec![Variable::LeftChild(1, 2), Variable::RightChild(1, 3)]));
-        assert!(rules._validate(&vec![Variable::RightChild(1, 2)]));
+        assert!(!rules._validate(&[Variable::LeftChild(1, 2), Variable::LeftChild(3, 2)]));
+        assert!(!rules._validate(&[Variable::LeftChild(1, 2), Variable::RightChild(1, 2)]));
+        assert!(!rules._validate(&[Variable::LeftChild(1, 2), Variable::RightChild(3, 2)]));
+        assert!(!rules._validate(&[Variable::RightChild(1, 2), Variable::RightChild(3, 2)]));
+        assert!(rules._validate(&[Variable::LeftChild(1, 3), Variable::LeftChild(2, 3)]));
+        assert!(rules._validate(&[Variable::LeftChild(1, 2), Variable::RightChild(1, 3)]));
+        assert!(rules._validate(&[Variable::RightChild(1, 2)]));
     }
 }
@@ -1,4 +1,4 @@
-use super::Enforcer;
+use super::{Enforcer, RuleSink};
 use crate::bool_logic::{PropExpr, Variable};
 use crate::context::Context;
 
@@ -13,20 +13,13 @@ impl AFASkTypeEnforcer {
 }
 
 impl Enforcer for AFASkTypeEnforcer {
-    fn rules(&self, _ctx: &Context) -> Vec<PropExpr> {
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
         let id = self.0;
-        let vars = vec![
-            Variable::And(id),
-            Variable::Or(id),
-            Variable::Next(id),
-            Variable::WNext(id),
-            Variable::Until(id),
-            Variable::Release(id),
-            Variable::Eventually(id),
-            Variable::Always(id),
-            Variable::Literal(id),
-        ];
-        vec![super::one_of(vars.into_iter())]
+        if !ctx.is_fresh(id) {
+            return;
+        }
+        let vars: Vec<Variable> = super::sk_types(ctx).map(|ty| ty(id)).collect();
+        sink.add_rule(super::one_of(vars.into_iter()));
     }
 }
 
@@ -42,10 +35,10 @@ impl AFASpecificStructureEnforcer {
 }
 
 impl Enforcer for AFASpecificStructureEnforcer {
-    fn rules(&self, ctx: &Context) -> Vec<PropExpr> {
-        let mut rules = Vec::new();
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
         let ty = &self.0;
         let vars: Vec<PropExpr> = (((self.0).skeleton_id() + 1)..ctx.max_skeletons())
+            .filter(|v| ctx.is_fresh(*v))
             .map(|v| ((self.0).skeleton_id(), v))
             .map(|(l, r)| (Variable::LeftChild(l, r), Variable::RightChild(l, r)))
             .filter_map(|var| {
@@ -61,10 +54,9 @@ impl Enforcer for AFASpecificStructureEnforcer {
                 }
             })
             .collect();
-        if vars.len() > 0 {
-            rules.push(self.0 >> PropExpr::chained_and(vars));
+        if !vars.is_empty() {
+            sink.add_rule(self.0 >> PropExpr::chained_and(vars));
         }
-        rules
     }
 }
 
@@ -72,6 +64,7 @@ impl Enforcer for AFASpecificStructureEnforcer {
 mod test {
     use super::*;
     use crate::context::Context;
+    use crate::ltl::Operator;
 
     #[test]
     fn sk_type() {
@@ -92,17 +85,39 @@ mod test {
             Variable::Always(id),
             Variable::Literal(id),
         ];
-        assert!(!rule._validate(&vec![]), "no assign should fail");
+        assert!(!rule._validate(&[]), "no assign should fail");
         for v in vars.iter() {
-            let model = vec![v.clone()];
+            let model = vec![*v];
             assert!(rule._validate(&model), "single assign should pass");
         }
         for v in vars.iter().skip(1) {
-            let model = vec![v.clone(), vars.first().unwrap().clone()];
+            let model = vec![*v, *vars.first().unwrap()];
             assert!(!rule._validate(&model), "double assign should fail");
         }
     }
 
+    #[test]
+    fn past_sk_type() {
+        let mut ctx = Context::with_bound(2);
+        let rule = PropExpr::chained_and(AFASkTypeEnforcer::new(1).rules(&ctx));
+        assert!(!rule._validate(&[Variable::Since(1)]));
+        ctx.set_operators(&[Operator::Until, Operator::Since]);
+        let rule = PropExpr::chained_and(AFASkTypeEnforcer::new(1).rules(&ctx));
+        assert!(rule._validate(&[Variable::Since(1)]));
+        assert!(!rule._validate(&[Variable::Since(1), Variable::Until(1)]));
+    }
+
+    #[test]
+    fn restricted_sk_type() {
+        let mut ctx = Context::with_bound(2);
+        ctx.set_operators(&[Operator::Always, Operator::Always, Operator::And]);
+        let rule = PropExpr::chained_and(AFASkTypeEnforcer::new(1).rules(&ctx));
+        assert!(rule._validate(&[Variable::Always(1)]));
+        assert!(rule._validate(&[Variable::Literal(1)]));
+        assert!(!rule._validate(&[Variable::Eventually(1)]));
+        assert!(!rule._validate(&[Variable::Or(1)]));
+    }
+
     #[test]
     fn and() {
         let ctx = Context::with_bound(3);
@@ -124,15 +139,15 @@ mod test {
             .expect("should have exactly one rule");
 
         assert!(
-            rule._validate(&vec![Variable::Next(1), Variable::LeftChild(1, 2)]),
+            rule._validate(&[Variable::Next(1), Variable::LeftChild(1, 2)]),
             "NEXT can have left child"
         );
         assert!(
-            !rule._validate(&vec![Variable::Next(1), Variable::RightChild(1, 2)]),
+            !rule._validate(&[Variable::Next(1), Variable::RightChild(1, 2)]),
             "NEXT can not have right child"
         );
         assert!(
-            rule._validate(&vec![Variable::And(1), Variable::RightChild(1, 2)]),
+            rule._validate(&[Variable::And(1), Variable::RightChild(1, 2)]),
             "not NEXT can have right child"
         );
     }
@@ -146,23 +161,23 @@ mod test {
             .expect("should have exactly one rule");
 
         assert!(
-            !rule._validate(&vec![Variable::Literal(1), Variable::LeftChild(1, 2)]),
+            !rule._validate(&[Variable::Literal(1), Variable::LeftChild(1, 2)]),
             "LIT can not have left child id=2"
         );
         assert!(
-            !rule._validate(&vec![Variable::Literal(1), Variable::RightChild(1, 2)]),
+            !rule._validate(&[Variable::Literal(1), Variable::RightChild(1, 2)]),
             "LIT can not have right child id=2"
         );
         assert!(
-            !rule._validate(&vec![Variable::Literal(1), Variable::LeftChild(1, 3)]),
+            !rule._validate(&[Variable::Literal(1), Variable::LeftChild(1, 3)]),
             "LIT can not have left child id=3"
         );
         assert!(
-            !rule._validate(&vec![Variable::Literal(1), Variable::RightChild(1, 3)]),
+            !rule._validate(&[Variable::Literal(1), Variable::RightChild(1, 3)]),
             "LIT can not have right child id=3"
         );
         assert!(
-            rule._validate(&vec![Variable::And(1), Variable::LeftChild(1, 2)]),
+            rule._validate(&[Variable::And(1), Variable::LeftChild(1, 2)]),
             "not LIT can have left child"
         );
     }
@@ -0,0 +1,82 @@
+use super::{Enforcer, RuleSink};
+use crate::bool_logic::Variable;
+use crate::context::Context;
+
+/// 消除同一公式的不同编号，要求节点按照广度优先的顺序编号
+///
+/// 任意公式树都存在满足要求的编号，因此不改变可满足性，只减少求解器需要排除的同构解：
+///
+/// - 节点的父节点编号随节点编号单调不减，即对于 `q < p < j < k`，`p` 是 `j` 的父节点与
+///   `q` 是 `k` 的父节点不能同时成立；
+/// - 二目节点的左子树编号小于右子树编号。
+pub struct SymmetryEnforcer;
+
+impl Enforcer for SymmetryEnforcer {
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        if !ctx.is_symmetry_breaking() {
+            return;
+        }
+        let n = ctx.max_skeletons();
+        let children: [fn(usize, usize) -> Variable; 2] =
+            [Variable::LeftChild, Variable::RightChild];
+        // 父节点编号单调不减
+        for k in (0..n).filter(|k| ctx.is_fresh(*k)) {
+            for j in 0..k {
+                for p in 0..j {
+                    for q in 0..p {
+                        for x in children.iter() {
+                            for y in children.iter() {
+                                sink.add_rule(!x(p, j) | !y(q, k));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // 左子树编号小于右子树编号
+        for s in 0..n {
+            for s2 in (s + 2)..n {
+                for s1 in ((s2 + 1)..n).filter(|s1| ctx.is_fresh(*s1)) {
+                    sink.add_rule(!Variable::LeftChild(s, s1) | !Variable::RightChild(s, s2));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bool_logic::PropExpr;
+
+    #[test]
+    fn bfs_order() {
+        let mut ctx = Context::with_bound(5);
+        assert!(SymmetryEnforcer.rules(&ctx).is_empty());
+        ctx.set_symmetry_breaking(true);
+        let rules = PropExpr::chained_and(SymmetryEnforcer.rules(&ctx));
+        use Variable::{LeftChild as L, RightChild as R};
+        // (0 (1 3 4) 2)
+        assert!(rules._validate(&[L(0, 1), R(0, 2), L(1, 3), R(1, 4)]));
+        // (0 3 (2 4))
+        assert!(!rules._validate(&[L(0, 3), R(0, 2), L(2, 4)]));
+        // (0 (1 2 4) 3)
+        assert!(!rules._validate(&[L(0, 1), R(0, 3), L(1, 2), R(1, 4)]));
+        // (0 (1 (2 3)) 4)
+        assert!(!rules._validate(&[L(0, 1), R(0, 4), L(1, 2), L(2, 3)]));
+        // (0 (1 (3 4)) 2)
+        assert!(rules._validate(&[L(0, 1), R(0, 2), L(1, 3), L(3, 4)]));
+    }
+
+    #[test]
+    fn incremental() {
+        let mut ctx = Context::with_bound(4);
+        ctx.set_symmetry_breaking(true);
+        let all = SymmetryEnforcer.rules(&ctx).len();
+        ctx.set_size_bound(3);
+        let old = SymmetryEnforcer.rules(&ctx).len();
+        ctx.set_encoded_skeletons(3);
+        ctx.set_size_bound(4);
+        assert_eq!(SymmetryEnforcer.rules(&ctx).len(), all - old);
+    }
+}
@@ -0,0 +1,220 @@
+use super::{Enforcer, RuleSink};
+use crate::bool_logic::{PropExpr, Variable};
+use crate::context::{Context, Example, SuffixTrie, Weight};
+
+/// 节点类型 `ty` 在后缀树节点 `n` 上的规则
+///
+/// 正例与反例共用后缀树节点，因此 `TrieRun` 与子树的语义等价，而不只是蕴含；有穷序列上的递归总会在
+/// 序列结束时终止，等价关系唯一确定所有 `TrieRun` 的取值。
+fn make_rule(
+    ctx: &Context,
+    trie: &SuffixTrie,
+    ty: Variable,
+    s1: usize,
+    s2: usize,
+    n: usize,
+) -> Vec<PropExpr> {
+    use Variable::*;
+    let node = trie.node(n);
+    let run = |s| TrieRun(n, s);
+    // 后继节点上的变量，序列在此结束时为 `None`
+    let next = |s| node.next().map(|n1| TrieRun(n1, s));
+    let iff = |s, rhs: PropExpr| PropExpr::biconditional(run(s).into(), rhs);
+    match ty {
+        Variable::And(s) => {
+            vec![iff(s, run(s1) & run(s2)) << (And(s) & LeftChild(s, s1) & RightChild(s, s2))]
+        }
+        Variable::Or(s) => {
+            vec![iff(s, run(s1) | run(s2)) << (Or(s) & LeftChild(s, s1) & RightChild(s, s2))]
+        }
+        Variable::Next(s) => vec![
+            (match next(s1) {
+                Some(v) => iff(s, v.into()),
+                None => !run(s),
+            }) << (Next(s) & LeftChild(s, s1)),
+        ],
+        Variable::WNext(s) => vec![
+            (match next(s1) {
+                Some(v) => iff(s, v.into()),
+                None => run(s).into(),
+            }) << (WNext(s) & LeftChild(s, s1)),
+        ],
+        Variable::Until(s) => vec![
+            iff(
+                s,
+                match next(s) {
+                    Some(v) => run(s2) | (run(s1) & v),
+                    None => run(s2).into(),
+                },
+            ) << (Until(s) & LeftChild(s, s1) & RightChild(s, s2)),
+        ],
+        Variable::Release(s) => vec![
+            iff(
+                s,
+                match next(s) {
+                    Some(v) => run(s2) & (run(s1) | v),
+                    None => run(s2).into(),
+                },
+            ) << (Release(s) & LeftChild(s, s1) & RightChild(s, s2)),
+        ],
+        Variable::Eventually(s) => vec![
+            iff(
+                s,
+                match next(s) {
+                    Some(v) => run(s1) | v,
+                    None => run(s1).into(),
+                },
+            ) << (Eventually(s) & LeftChild(s, s1)),
+        ],
+        Variable::Always(s) => vec![
+            iff(
+                s,
+                match next(s) {
+                    Some(v) => run(s1) & v,
+                    None => run(s1).into(),
+                },
+            ) << (Always(s) & LeftChild(s, s1)),
+        ],
+        Variable::Not(s) => vec![iff(s, !run(s1)) << (Not(s) & LeftChild(s, s1))],
+        Variable::Implies(s) => {
+            vec![iff(s, !run(s1) | run(s2)) << (Implies(s) & LeftChild(s, s1) & RightChild(s, s2))]
+        }
+        Variable::Literal(s) => (0..ctx.word_count())
+            .flat_map(|v| {
+                let holds = node.contains(v);
+                vec![
+                    (if holds { run(s).into() } else { !run(s) })
+                        << (Literal(s) & Word(s, v, true)),
+                    (if holds { !run(s) } else { run(s).into() })
+                        << (Literal(s) & Word(s, v, false)),
+                ]
+            })
+            .collect(),
+        Variable::True(s) => vec![run(s) << True(s)],
+        Variable::False(s) => vec![!run(s) << False(s)],
+        _ => unreachable!(), // 过去时运算符不使用后缀树
+    }
+}
+
+/// 确保给定类型的节点在后缀树节点上的取值与其语义一致的规则，正例与反例共用
+#[derive(Debug, Copy, Clone)]
+pub struct TrieEnforcer(Variable, usize);
+
+impl TrieEnforcer {
+    /// 构造节点类型 `ty` 在后缀树节点 `n` 上的 Enforcer
+    pub fn new(ty: Variable, n: usize) -> Self {
+        Self(ty, n)
+    }
+}
+
+impl Enforcer for TrieEnforcer {
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        let trie = ctx.trie().expect("后缀树尚未建立");
+        let rules = |s1, s2| make_rule(ctx, trie, self.0, s1, s2, self.1);
+        if self.0.is_atom() && ctx.is_fresh(self.0.skeleton_id()) {
+            for rule in rules(0x7F7F7F7F, 0x7F7F7F7F) {
+                sink.add_rule(rule);
+            }
+        }
+        if self.0.is_unary() {
+            for s1 in (self.0.skeleton_id() + 1)..ctx.max_skeletons() {
+                if ctx.is_fresh(s1) {
+                    for rule in rules(s1, 0x7F7F7F7F) {
+                        sink.add_rule(rule);
+                    }
+                }
+            }
+        }
+        if self.0.is_binary() {
+            for s1 in (self.0.skeleton_id() + 1)..ctx.max_skeletons() {
+                for s2 in (self.0.skeleton_id() + 2)..ctx.max_skeletons() {
+                    if ctx.is_fresh(s1.max(s2)) {
+                        for rule in rules(s1, s2) {
+                            sink.add_rule(rule);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 要求后缀树中的例子的根节点接受正例、拒绝反例
+#[derive(Debug, Copy, Clone)]
+pub struct TrieRootEnforcer<'a>(&'a Example);
+
+impl<'a> TrieRootEnforcer<'a> {
+    /// 构造例子 `ex` 的 Enforcer，`ex` 需要在后缀树中
+    pub fn new(ex: &'a Example) -> Self {
+        Self(ex)
+    }
+}
+
+impl<'a> Enforcer for TrieRootEnforcer<'a> {
+    fn generate(&self, ctx: &Context, sink: &mut dyn RuleSink) {
+        if !ctx.is_fresh(0) {
+            return;
+        }
+        let e = self.0.id();
+        let root = ctx
+            .trie()
+            .and_then(|trie| trie.root(e))
+            .expect("例子不在后缀树中");
+        let accept = if self.0.is_pos() {
+            Variable::TrieRun(root, 0).into()
+        } else {
+            !Variable::TrieRun(root, 0)
+        };
+        // 必须被满足的例子不能放弃
+        sink.add_rule(
+            if ctx.is_noise_tolerant() && self.0.weight() != Weight::Hard {
+                accept | Variable::Relax(e)
+            } else {
+                accept
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::trace;
+
+    /// `F p` 在 `{} {p}` 与 `{p}` 上成立，在 `{}` 上不成立
+    #[test]
+    fn eventually() {
+        let mut ctx = Context::with_bound(2);
+        let p = ctx.get_word_id("p".to_string());
+        ctx.add_example(trace(&[&[], &[p]]), true, Weight::default(), None);
+        ctx.add_example(trace(&[&[]]), false, Weight::default(), None);
+        ctx.build_trie();
+        let trie = ctx.trie().unwrap();
+        // 节点 0 为 `{p}`，1 为 `{} {p}`，2 为 `{}`
+        assert_eq!(
+            (trie.len(), trie.root(0), trie.root(1)),
+            (3, Some(1), Some(2))
+        );
+        use Variable::*;
+        let mut model = vec![Eventually(0), LeftChild(0, 1), Literal(1), Word(1, p, true)];
+        model.extend_from_slice(&[TrieRun(0, 0), TrieRun(0, 1), TrieRun(1, 0)]);
+        let mut rules = vec![];
+        for n in 0..trie.len() {
+            rules.extend(TrieEnforcer::new(Eventually(0), n).rules(&ctx));
+            rules.extend(TrieEnforcer::new(Literal(1), n).rules(&ctx));
+        }
+        for ex in ctx.examples() {
+            rules.extend(TrieRootEnforcer::new(ex).rules(&ctx));
+        }
+        assert!(rules.iter().all(|r| r._validate(&model)));
+        // 任何一个 `TrieRun` 取值不同都会违反规则
+        for i in 4..model.len() {
+            let mut wrong = model.clone();
+            wrong.remove(i);
+            assert!(!rules.iter().all(|r| r._validate(&wrong)));
+        }
+        let mut wrong = model.clone();
+        wrong.push(TrieRun(2, 0));
+        assert!(!rules.iter().all(|r| r._validate(&wrong)));
+    }
+}
@@ -0,0 +1,1673 @@
+//! 根据输入序列学习 LTL 公式
+//!
+//! 从尺寸限制 `2` 开始依次尝试，直到找到可满足的尺寸限制或超过 `LearnOptions::max_size`；
+//! `LearnOptions::jobs` 大于 `1` 时同时求解多个尺寸限制，结果与依次求解相同。
+//! 求得的公式将在所有例子上重新求值，若与例子不符则报告 `LearnError::Mismatch`。
+//! 超过 `LearnOptions::timeout` 或 `LearnOptions::bound_timeout` 时停止，并通过
+//! `LearnError::Timeout` 报告已证明不可满足的最大尺寸限制。
+//! 每个尺寸限制的规则数量、求解器规模与用时记录在 `BoundStats` 中，见 `learn_with_stats`。
+
+use crate::bool_logic::{PropExpr, Variable};
+use crate::context::{Context, Input, InputError, Weight};
+use crate::enforcers::{ContextEnforcer, Enforcer, RuleSink};
+use crate::ltl::{self, DecodeError, LtlNode, Operator};
+use crate::sat::{
+    convert_cnf_local, convert_tseitin_local, Backend, EncodingError, SATConverter, SolverError,
+};
+use crate::stats::BoundStats;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{collections::HashSet, convert::TryFrom, io::Write};
+
+/// 求解过程中写出的中间结果
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Dump {
+    /// 每个尺寸限制下生成的逻辑表达式
+    Expr,
+    /// 每个尺寸限制下的 DIMACS CNF
+    Cnf,
+    /// 每个尺寸限制下的求解结果
+    Result,
+}
+
+/// 例子的编码方式
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub enum Encoding {
+    /// 逐个例子编码，每个例子的每个时间点使用各自的 `Run` 变量（默认）
+    #[default]
+    Example,
+    /// 以所有有穷例子的后缀树编码，见 `Context::build_trie`；套索序列依然逐个例子编码，
+    /// 使用过去时运算符时报告 `InputError::PastTimeTrie`
+    Trie,
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Encoding::Example => f.write_str("example"),
+            Encoding::Trie => f.write_str("trie"),
+        }
+    }
+}
+
+/// 未知的例子编码方式
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UnknownEncoding(pub String);
+
+impl std::fmt::Display for UnknownEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "未知的例子编码 {:?}，可选 example 或 trie", self.0)
+    }
+}
+
+impl std::error::Error for UnknownEncoding {}
+
+impl std::str::FromStr for Encoding {
+    type Err = UnknownEncoding;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "example" => Ok(Encoding::Example),
+            "trie" => Ok(Encoding::Trie),
+            s => Err(UnknownEncoding(s.to_string())),
+        }
+    }
+}
+
+/// 学习选项
+#[derive(Debug, Clone)]
+pub struct LearnOptions {
+    /// 尺寸限制的上界，将依次尝试 `2..=max_size`
+    pub max_size: usize,
+    /// 在不同尺寸限制之间增量求解，复用求解器状态与学习到的子句
+    pub incremental: bool,
+    /// 使用 Tseitin 变换生成 CNF，而不是按分配律展开
+    pub tseitin: bool,
+    /// 宽松模式，将例子中出现的单词表以外的单词加入单词表，而不是报告错误
+    pub lenient: bool,
+    /// 编码中可以使用的运算符，`None` 表示所有将来时运算符；字面量总是可用
+    pub operators: Option<Vec<Operator>>,
+    /// 要求节点按照广度优先的顺序编号，减少求解器需要排除的同构解
+    pub symmetry_breaking: bool,
+    /// 额外使用过去时运算符 `Y`、`Z`、`S`、`T`、`O`、`H`
+    pub past_time: bool,
+    /// 允许例子不被满足，并使不被满足的例子的代价（权重）之和最小
+    pub noise_tolerant: bool,
+    /// 允许不被满足的例子的代价之和至多为指定值，此时不再使其最小
+    pub max_errors: Option<usize>,
+    /// 合并序列与循环起点都相同的同类例子，见 `Context::merge_duplicates`
+    pub dedup: bool,
+    /// 在后缀相同的同类有穷例子之间共享 `Run` 变量，见 `Context::share_suffixes`；
+    /// 使用过去时运算符或后缀树编码时无效
+    pub share_suffixes: bool,
+    /// 例子的编码方式
+    pub encoding: Encoding,
+    /// 在求得公式的尺寸限制下最多求得的不同公式数量
+    pub max_solutions: usize,
+    /// 使用的 SAT 求解器
+    pub solver: Backend,
+    /// 同时求解的尺寸限制数量，大于 `1` 时每个尺寸限制使用独立的 `Context` 与求解器，
+    /// 并忽略 `incremental`；求得的结果与依次求解相同
+    pub jobs: usize,
+    /// 生成例子规则的线程数量，大于 `1` 时每个尺寸限制使用各自的线程池，每批并行生成
+    /// `encode_jobs` 个例子的规则，需要同时保存这些规则；生成的规则与依次生成相同
+    pub encode_jobs: usize,
+    /// 整个学习过程的时限；`Backend::Varisat` 无法中断，只在每次求解开始前检查
+    pub timeout: Option<Duration>,
+    /// 每个尺寸限制（包括生成规则与求解）的时限；生成规则的过程不会被中断，
+    /// `Backend::Varisat` 同样只在每次求解开始前检查
+    pub bound_timeout: Option<Duration>,
+    /// 写出的中间结果
+    pub dump: Option<Dump>,
+    /// 在标准错误输出每个尺寸限制的求解情况与统计
+    pub verbose: bool,
+}
+
+impl Default for LearnOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 10,
+            incremental: false,
+            tseitin: false,
+            lenient: false,
+            operators: None,
+            symmetry_breaking: false,
+            past_time: false,
+            noise_tolerant: false,
+            max_errors: None,
+            dedup: false,
+            share_suffixes: false,
+            encoding: Encoding::Example,
+            max_solutions: 1,
+            solver: Backend::Minisat,
+            jobs: 1,
+            encode_jobs: 1,
+            timeout: None,
+            bound_timeout: None,
+            dump: None,
+            verbose: false,
+        }
+    }
+}
+
+/// 学习结果
+#[derive(Debug, Eq, PartialEq)]
+pub struct LearnResult {
+    /// 学习到的公式
+    pub formula: LtlNode,
+    /// 求得公式时的尺寸限制
+    pub size: usize,
+    /// 同一尺寸限制下求得的其它公式，不含 `formula`，且互不相同
+    pub alternatives: Vec<LtlNode>,
+    /// `formula` 不接受的正例序号
+    pub sacrificed_pos: Vec<usize>,
+    /// `formula` 接受的反例序号
+    pub sacrificed_neg: Vec<usize>,
+    /// 不被满足的例子的代价之和
+    pub sacrificed_cost: usize,
+}
+
+/// 学习在得到结果之前停止的原因
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum StopReason {
+    /// 超过整个学习过程的时限 `LearnOptions::timeout`
+    Timeout,
+    /// 超过单个尺寸限制的时限 `LearnOptions::bound_timeout`
+    BoundTimeout,
+}
+
+impl std::fmt::Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopReason::Timeout => f.write_str("超过总时限"),
+            StopReason::BoundTimeout => f.write_str("超过单个尺寸限制的时限"),
+        }
+    }
+}
+
+/// 学习失败的原因
+#[derive(Debug)]
+pub enum LearnError {
+    /// 输入不合法
+    Input(InputError),
+    /// 在给定的尺寸限制内均不可满足
+    Unsatisfiable(usize),
+    /// 超过时限，停止时尚未求得公式
+    Timeout {
+        /// 停止的原因
+        reason: StopReason,
+        /// 停止时正在求解的尺寸限制
+        bound: usize,
+        /// 已证明不可满足的最大尺寸限制，即在此以内不存在符合例子的公式
+        unsat: Option<usize>,
+    },
+    /// 生成的规则不符合编码要求
+    Encoding(EncodingError),
+    /// 无法从求解结果构造公式
+    Decode(DecodeError),
+    /// 求得的公式与例子不符，意味着编码存在错误
+    Mismatch {
+        /// 求得的公式
+        formula: LtlNode,
+        /// 不符的例子是否为正例
+        positive: bool,
+        /// 不符的例子在正例（反例）中的序号
+        trace: usize,
+        /// 不符的例子，每个时间点的单词按名称排序
+        sequence: Vec<Vec<String>>,
+    },
+    /// 读写失败
+    Io(std::io::Error),
+    /// SAT 求解器运行失败
+    Solver(SolverError),
+}
+
+impl std::fmt::Display for LearnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LearnError::Input(e) => e.fmt(f),
+            LearnError::Unsatisfiable(n) => write!(f, "无法在给定限制内求解，n <= {}", n),
+            LearnError::Timeout {
+                reason,
+                bound,
+                unsat,
+            } => match unsat {
+                Some(m) => write!(
+                    f,
+                    "求解 n = {} 时{}，已证明 n <= {} 时不可满足",
+                    bound, reason, m
+                ),
+                None => write!(f, "求解 n = {} 时{}", bound, reason),
+            },
+            LearnError::Encoding(e) => e.fmt(f),
+            LearnError::Decode(e) => e.fmt(f),
+            LearnError::Mismatch {
+                formula,
+                positive,
+                trace,
+                sequence,
+            } => write!(
+                f,
+                "求得的公式 {} {}第 {} 个{} {:?}，编码存在错误",
+                formula,
+                if *positive { "不接受" } else { "接受" },
+                trace,
+                if *positive { "正例" } else { "反例" },
+                sequence
+            ),
+            LearnError::Io(e) => write!(f, "读写失败：{}", e),
+            LearnError::Solver(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LearnError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LearnError::Input(e) => Some(e),
+            LearnError::Unsatisfiable(_) => None,
+            LearnError::Timeout { .. } => None,
+            LearnError::Encoding(e) => Some(e),
+            LearnError::Decode(e) => Some(e),
+            LearnError::Mismatch { .. } => None,
+            LearnError::Io(e) => Some(e),
+            LearnError::Solver(e) => Some(e),
+        }
+    }
+}
+
+impl From<InputError> for LearnError {
+    fn from(e: InputError) -> Self {
+        LearnError::Input(e)
+    }
+}
+
+impl From<EncodingError> for LearnError {
+    fn from(e: EncodingError) -> Self {
+        LearnError::Encoding(e)
+    }
+}
+
+impl From<DecodeError> for LearnError {
+    fn from(e: DecodeError) -> Self {
+        LearnError::Decode(e)
+    }
+}
+
+impl From<SolverError> for LearnError {
+    fn from(e: SolverError) -> Self {
+        LearnError::Solver(e)
+    }
+}
+
+impl From<std::io::Error> for LearnError {
+    fn from(e: std::io::Error) -> Self {
+        LearnError::Io(e)
+    }
+}
+
+/// 学习符合输入的 LTL 公式
+pub fn learn(input: Input, opts: LearnOptions) -> Result<LearnResult, LearnError> {
+    learn_with_output(input, opts, &mut std::io::sink())
+}
+
+/// 学习符合输入的 LTL 公式，并将 `LearnOptions::dump` 指定的中间结果写入 `output`
+pub fn learn_with_output(
+    input: Input,
+    opts: LearnOptions,
+    output: &mut impl Write,
+) -> Result<LearnResult, LearnError> {
+    learn_with_stats(input, opts, output, &mut vec![])
+}
+
+/// 同 `learn_with_output`，并在 `stats` 中按求解顺序记录每个尺寸限制的统计；
+/// 学习失败时 `stats` 中依然保留已经求解的尺寸限制的统计
+pub fn learn_with_stats(
+    mut input: Input,
+    opts: LearnOptions,
+    output: &mut impl Write,
+    stats: &mut Vec<BoundStats>,
+) -> Result<LearnResult, LearnError> {
+    let total = opts.timeout.map(|t| Instant::now() + t);
+    if opts.lenient {
+        for word in input.extend_vocab() {
+            if opts.verbose {
+                eprintln!("{}，已加入单词表", word);
+            }
+        }
+    }
+    let mut operators = match opts.operators.as_ref() {
+        Some(ops) => ops.clone(),
+        None => Operator::FUTURE.to_vec(),
+    };
+    if opts.past_time {
+        operators.extend_from_slice(Operator::PAST);
+    }
+    // 过去时运算符与之前的时间点有关，后缀相同的时间点不一定等价
+    let past_time = operators.iter().any(|op| Operator::PAST.contains(op));
+    if past_time && opts.encoding == Encoding::Trie {
+        return Err(InputError::PastTimeTrie.into());
+    }
+    // 套索序列上的过去时运算符与循环的展开次数有关，无法按时间点编码
+    if past_time {
+        let pos = input.lassos_pos().iter().position(Option::is_some);
+        let neg = input.lassos_neg().iter().position(Option::is_some);
+        if let Some(trace) = pos {
+            return Err(InputError::PastTimeLasso {
+                positive: true,
+                trace,
+            }
+            .into());
+        }
+        if let Some(trace) = neg {
+            return Err(InputError::PastTimeLasso {
+                positive: false,
+                trace,
+            }
+            .into());
+        }
+    }
+    let mut ctx = Context::try_from(input)?;
+    ctx.set_operators(&operators);
+    let contradictions = ctx.contradictions();
+    if !contradictions.is_empty() {
+        // 允许例子不被满足时，只有双方都必须被满足的矛盾无法求得公式
+        let fatal: Vec<_> = if opts.noise_tolerant || opts.max_errors.is_some() {
+            contradictions.iter().filter(|c| c.hard).cloned().collect()
+        } else {
+            contradictions.clone()
+        };
+        if !fatal.is_empty() {
+            return Err(InputError::Contradictory(fatal).into());
+        }
+        if opts.verbose {
+            for c in contradictions.iter() {
+                eprintln!("{}，至少一方不被满足", c);
+            }
+        }
+    }
+    if opts.dedup {
+        let merged = ctx.merge_duplicates();
+        if opts.verbose {
+            eprintln!("合并了 {} 个重复的例子", merged);
+        }
+    }
+    if opts.encoding == Encoding::Trie {
+        let nodes = ctx.build_trie();
+        if opts.verbose {
+            eprintln!("后缀树共 {} 个节点", nodes);
+        }
+    } else if opts.share_suffixes && !past_time {
+        let shared = ctx.share_suffixes();
+        if opts.verbose {
+            eprintln!("{} 个时间点与其它例子共享 Run 变量", shared);
+        }
+    }
+    ctx.set_symmetry_breaking(opts.symmetry_breaking);
+    if let Some(k) = opts.max_errors {
+        ctx.set_max_errors(Some(k));
+    } else if opts.noise_tolerant {
+        let mut phase = BoundStats {
+            min_errors: true,
+            ..BoundStats::new(opts.max_size)
+        };
+        let k = min_errors(&mut ctx, &opts, total, output, &mut phase);
+        record(stats, phase, &opts);
+        ctx.set_max_errors(Some(k?));
+    }
+
+    if opts.jobs > 1 {
+        return learn_parallel(&ctx, &opts, total, output, stats);
+    }
+
+    let mut conv = new_converter(&opts);
+    for n in 2..=opts.max_size {
+        ctx.set_size_bound(n);
+        if !opts.incremental {
+            conv = new_converter(&opts);
+        }
+        let deadline = Deadline::start(total, &opts);
+        conv.set_deadline(deadline.map(|d| d.at));
+        let mut bound_stats = BoundStats::new(n);
+        let result = if deadline.is_some_and(|d| d.passed()) {
+            Err(SolverError::Timeout.into())
+        } else {
+            solve_bound(&ctx, &mut conv, &opts, output, &mut bound_stats)
+        };
+        record(stats, bound_stats, &opts);
+        if let Some(result) = stop_on_timeout(result, deadline, n, proven_unsat(n))? {
+            return Ok(result);
+        }
+        if opts.incremental {
+            // 保留求解器状态，并使只在该尺寸限制下成立的规则永久失效
+            let (cnf, phantoms) = convert_cnf_local(!Variable::Bound(n));
+            conv.add_local_clause(cnf, phantoms)?;
+            ctx.set_encoded_skeletons(n);
+        }
+    }
+
+    Err(LearnError::Unsatisfiable(opts.max_size))
+}
+
+/// 同时求解多个尺寸限制，每个尺寸限制使用独立的 `Context` 与求解器；某个尺寸限制可满足
+/// （或出错）后取消所有更大的尺寸限制，并按从小到大的顺序选取第一个不是不可满足的结果，
+/// 因此结果与不使用增量求解时依次求解相同
+fn learn_parallel(
+    ctx: &Context,
+    opts: &LearnOptions,
+    total: Option<Instant>,
+    output: &mut impl Write,
+    stats: &mut Vec<BoundStats>,
+) -> Result<LearnResult, LearnError> {
+    let bounds: Vec<usize> = (2..=opts.max_size).collect();
+    let cancel: Vec<Arc<AtomicBool>> = bounds
+        .iter()
+        .map(|_| Arc::new(AtomicBool::new(false)))
+        .collect();
+    // 已经得到结果的最小尺寸限制，更大的尺寸限制无需再求解
+    let decided = AtomicUsize::new(usize::MAX);
+    // 每个尺寸限制的结果、写出的中间结果与统计
+    type Outcome = (Result<Option<LearnResult>, LearnError>, Vec<u8>, BoundStats);
+    let outcomes: Vec<Mutex<Option<Outcome>>> = bounds.iter().map(|_| Mutex::new(None)).collect();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opts.jobs)
+        .build()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    pool.scope_fifo(|scope| {
+        for (i, &n) in bounds.iter().enumerate() {
+            let (cancel, decided, outcomes) = (&cancel, &decided, &outcomes);
+            scope.spawn_fifo(move |_| {
+                if n > decided.load(Ordering::SeqCst) {
+                    return;
+                }
+                let mut ctx = ctx.clone();
+                ctx.set_size_bound(n);
+                let mut conv = new_converter(opts);
+                let deadline = Deadline::start(total, opts);
+                conv.set_deadline(deadline.map(|d| d.at));
+                conv.set_cancel(cancel[i].clone());
+                let mut buffer = vec![];
+                let mut bound_stats = BoundStats::new(n);
+                let result = if deadline.is_some_and(|d| d.passed()) {
+                    Err(SolverError::Timeout.into())
+                } else {
+                    solve_bound(&ctx, &mut conv, opts, &mut buffer, &mut bound_stats)
+                };
+                let result = stop_on_timeout(result, deadline, n, proven_unsat(n));
+                if !matches!(result, Ok(None)) {
+                    decided.fetch_min(n, Ordering::SeqCst);
+                    for c in cancel[i + 1..].iter() {
+                        c.store(true, Ordering::SeqCst);
+                    }
+                }
+                *outcomes[i].lock().unwrap() = Some((result, buffer, bound_stats));
+            });
+        }
+    });
+
+    for outcome in outcomes.into_iter() {
+        // 更小的尺寸限制均不可满足时，该尺寸限制不会被跳过或取消
+        let (result, buffer, bound_stats) = outcome
+            .into_inner()
+            .unwrap()
+            .expect("更小的尺寸限制均不可满足");
+        output.write_all(&buffer)?;
+        record(stats, bound_stats, opts);
+        if let Some(result) = result? {
+            return Ok(result);
+        }
+    }
+    Err(LearnError::Unsatisfiable(opts.max_size))
+}
+
+/// 记录一个尺寸限制的统计
+fn record(stats: &mut Vec<BoundStats>, bound_stats: BoundStats, opts: &LearnOptions) {
+    if opts.verbose {
+        eprintln!("{}", bound_stats);
+    }
+    stats.push(bound_stats);
+}
+
+/// 尺寸限制 `n` 之前的尺寸限制均不可满足时，已证明不可满足的最大尺寸限制
+fn proven_unsat(n: usize) -> Option<usize> {
+    if n > 2 {
+        Some(n - 1)
+    } else {
+        None
+    }
+}
+
+/// 在当前尺寸限制下求解，可满足时在同一尺寸限制下继续求得至多 `max_solutions` 个公式
+fn solve_bound(
+    ctx: &Context,
+    conv: &mut SATConverter,
+    opts: &LearnOptions,
+    output: &mut impl Write,
+    stats: &mut BoundStats,
+) -> Result<Option<LearnResult>, LearnError> {
+    let mut solution = match solve_iter(ctx, conv, opts, output, stats)? {
+        Some(solution) => solution,
+        None => return Ok(None),
+    };
+    let (sacrificed_pos, sacrificed_neg, sacrificed_cost) = check(ctx, &solution)?;
+    let mut formulas = vec![solution.formula.clone()];
+    while formulas.len() < opts.max_solutions {
+        // 排除已求得的公式，在同一尺寸限制下继续求解
+        let blocking = solution.structure.into_iter().map(|v| !v).collect();
+        let (cnf, phantoms) = convert_cnf_local(PropExpr::chained_or(blocking));
+        conv.add_local_clause(cnf, phantoms)?;
+        solution = match solve_model(ctx, conv, opts, output, stats) {
+            Ok(Some(solution)) => solution,
+            Ok(None) => break,
+            // 已经求得公式，超时只影响其它公式的数量
+            Err(LearnError::Solver(SolverError::Timeout)) => {
+                if opts.verbose {
+                    eprintln!("求解其它公式时超时，已求得 {} 个公式", formulas.len());
+                }
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        check(ctx, &solution)?;
+        // 结构不同的解可能对应相同的公式
+        if !formulas.contains(&solution.formula) {
+            formulas.push(solution.formula.clone());
+        }
+    }
+    let formula = formulas.remove(0);
+    Ok(Some(LearnResult {
+        formula,
+        size: ctx.max_skeletons(),
+        alternatives: formulas,
+        sacrificed_pos,
+        sacrificed_neg,
+        sacrificed_cost,
+    }))
+}
+
+/// 在最大的尺寸限制下求得不被满足的例子的最小代价之和；尺寸限制越大可表达的公式越多，
+/// 因此该值也是所有尺寸限制下的最小值
+fn min_errors(
+    ctx: &mut Context,
+    opts: &LearnOptions,
+    total: Option<Instant>,
+    output: &mut impl Write,
+    stats: &mut BoundStats,
+) -> Result<usize, LearnError> {
+    let total_cost = ctx.total_cost();
+    ctx.set_size_bound(opts.max_size);
+    ctx.set_max_errors(Some(0));
+    let mut conv = new_converter(opts);
+    // 整个过程视为在最大的尺寸限制下求解
+    let deadline = Deadline::start(total, opts);
+    conv.set_deadline(deadline.map(|d| d.at));
+    let mut solution = stop_on_timeout(
+        solve_iter(ctx, &mut conv, opts, output, stats),
+        deadline,
+        opts.max_size,
+        None,
+    )?;
+    while solution.is_none() {
+        let k = ctx.max_errors().unwrap_or_default() + 1;
+        if k > total_cost {
+            return Err(LearnError::Unsatisfiable(opts.max_size));
+        }
+        ctx.set_max_errors(Some(k));
+        solution = stop_on_timeout(
+            solve_model(ctx, &mut conv, opts, output, stats),
+            deadline,
+            opts.max_size,
+            None,
+        )?;
+    }
+    Ok(ctx.max_errors().unwrap_or_default())
+}
+
+/// 求解一个尺寸限制时的截止时间
+#[derive(Debug, Copy, Clone)]
+struct Deadline {
+    at: Instant,
+    /// 超过截止时间时停止的原因
+    reason: StopReason,
+}
+
+impl Deadline {
+    /// 从现在开始求解一个尺寸限制的截止时间，取总的截止时间 `total` 与单个尺寸限制的时限中
+    /// 较早的一个
+    fn start(total: Option<Instant>, opts: &LearnOptions) -> Option<Self> {
+        let total = total.map(|at| Deadline {
+            at,
+            reason: StopReason::Timeout,
+        });
+        let bound = opts.bound_timeout.map(|t| Deadline {
+            at: Instant::now() + t,
+            reason: StopReason::BoundTimeout,
+        });
+        match (total, bound) {
+            (Some(total), Some(bound)) if bound.at < total.at => Some(bound),
+            (total, bound) => total.or(bound),
+        }
+    }
+
+    fn passed(&self) -> bool {
+        Instant::now() >= self.at
+    }
+}
+
+/// 将求解器超时转换为 `LearnError::Timeout`
+fn stop_on_timeout<T>(
+    result: Result<T, LearnError>,
+    deadline: Option<Deadline>,
+    bound: usize,
+    unsat: Option<usize>,
+) -> Result<T, LearnError> {
+    match (result, deadline) {
+        (Err(LearnError::Solver(SolverError::Timeout)), Some(deadline)) => {
+            Err(LearnError::Timeout {
+                reason: deadline.reason,
+                bound,
+                unsat,
+            })
+        }
+        (result, _) => result,
+    }
+}
+
+/// 检验求得的公式接受所有正例且拒绝所有反例，允许例子不被满足时返回不被满足的正例与反例的序号，
+/// 以及它们的代价之和
+fn check(
+    ctx: &Context,
+    solution: &Solution,
+) -> Result<(Vec<usize>, Vec<usize>, usize), LearnError> {
+    let formula = &solution.formula;
+    let mut names = vec![String::new(); ctx.word_count()];
+    for (name, id) in ctx.words() {
+        names[*id] = name.clone();
+    }
+    let (mut sacrificed_pos, mut sacrificed_neg, mut cost) = (vec![], vec![], 0);
+    for ex in ctx.examples() {
+        let trace: Vec<HashSet<String>> = (0..ex.size())
+            .map(|t| ex.words_at(t).map(|w| names[w].clone()).collect())
+            .collect();
+        if formula.eval_lasso(&trace, ex.lasso(), 0) != ex.is_pos() {
+            // 必须满足的例子没有松弛约束，其 Relax 变量的取值没有意义
+            if ex.weight() == Weight::Hard || !solution.relaxed.contains(&ex.id()) {
+                return Err(LearnError::Mismatch {
+                    formula: formula.clone(),
+                    positive: ex.is_pos(),
+                    trace: ex.traces()[0],
+                    sequence: trace
+                        .into_iter()
+                        .map(|step| {
+                            let mut step: Vec<String> = step.into_iter().collect();
+                            step.sort();
+                            step
+                        })
+                        .collect(),
+                });
+            }
+            cost += ex.weight().cost();
+            if ex.is_pos() {
+                sacrificed_pos.extend_from_slice(ex.traces());
+            } else {
+                sacrificed_neg.extend_from_slice(ex.traces());
+            }
+        }
+    }
+    // 合并重复的例子后，例子的顺序与输入中的顺序不一定相同
+    sacrificed_pos.sort_unstable();
+    sacrificed_neg.sort_unstable();
+    Ok((sacrificed_pos, sacrificed_neg, cost))
+}
+
+fn new_converter(opts: &LearnOptions) -> SATConverter {
+    let conv = SATConverter::with_backend(opts.solver.create());
+    if opts.dump == Some(Dump::Cnf) {
+        conv.with_dimacs()
+    } else {
+        conv
+    }
+}
+
+/// 求得的解
+struct Solution {
+    /// 公式
+    formula: LtlNode,
+    /// 公式涉及的结构变量
+    structure: Vec<Variable>,
+    /// 允许不被满足的例子编号
+    relaxed: Vec<usize>,
+}
+
+/// 求解时的假设，只在当前尺寸限制下成立的规则由 `Bound(n)` 激活，
+/// 并通过 `Counter` 限制不被满足的例子的代价之和
+fn assumptions(ctx: &Context) -> Vec<(Variable, bool)> {
+    let mut assumptions = vec![
+        (Variable::Exactly(true), true),
+        (Variable::Exactly(false), false),
+        (Variable::Bound(ctx.max_skeletons()), true),
+    ];
+    let examples = ctx.examples().count();
+    if let Some(k) = ctx.max_errors() {
+        if k < ctx.total_cost() {
+            assumptions.push((Variable::Counter(examples - 1, k), false));
+        }
+    }
+    assumptions
+}
+
+/// 转换为 CNF 的规则，其中的无关变量在规则内编号，加入求解器时由 `SATConverter::add_local_clause` 编号
+struct Converted {
+    /// 导出逻辑表达式时规则的文本
+    expr: Option<String>,
+    cnf: PropExpr,
+    phantoms: usize,
+}
+
+impl Converted {
+    fn new(rule: PropExpr, opts: &LearnOptions) -> Self {
+        let expr = if opts.dump == Some(Dump::Expr) {
+            Some(format!("{:?}", rule))
+        } else {
+            None
+        };
+        let (cnf, phantoms) = if opts.tseitin {
+            convert_tseitin_local(rule)
+        } else {
+            convert_cnf_local(rule)
+        };
+        Self {
+            expr,
+            cnf,
+            phantoms,
+        }
+    }
+}
+
+/// 将生成的规则逐条转换为 CNF 并加入求解器，从而不必保存所有规则
+struct ConverterSink<'a, W> {
+    conv: &'a mut SATConverter,
+    opts: &'a LearnOptions,
+    output: &'a mut W,
+    stats: &'a mut BoundStats,
+    /// 当前规则生成器的名称
+    enforcer: &'static str,
+    /// 规则只在当前尺寸限制下成立时，激活规则的 `Bound(n)`
+    bound: Option<Variable>,
+    /// 第一个错误，出错后忽略之后的规则
+    error: Option<LearnError>,
+}
+
+impl<W: Write> ConverterSink<'_, W> {
+    /// 加入已经转换的规则
+    fn add_converted(&mut self, rule: Converted) {
+        if self.error.is_some() {
+            return;
+        }
+        self.stats.add_rules(self.enforcer, 1);
+        if let Err(err) = self.try_add(rule) {
+            self.error = Some(err);
+        }
+    }
+
+    /// 取出第一个错误，在每个阶段之后调用，出错时不再生成之后的规则
+    fn take_error(&mut self) -> Result<(), LearnError> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn try_add(&mut self, rule: Converted) -> Result<(), LearnError> {
+        if let Some(expr) = rule.expr {
+            writeln!(self.output, "{}", expr)?;
+        }
+        self.conv.add_local_clause(rule.cnf, rule.phantoms)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RuleSink for ConverterSink<'_, W> {
+    fn add_rule(&mut self, rule: PropExpr) {
+        if self.error.is_some() {
+            return;
+        }
+        let rule = match self.bound {
+            Some(bound) => bound >> rule,
+            None => rule,
+        };
+        self.add_converted(Converted::new(rule, self.opts));
+    }
+
+    fn enforcer(&mut self, name: &'static str) {
+        self.enforcer = name;
+        self.stats.add_rules(name, 0);
+    }
+}
+
+/// 在工作线程中生成并转换一个例子或后缀树节点的规则
+struct ExampleRules<'a> {
+    opts: &'a LearnOptions,
+    enforcer: &'static str,
+    rules: Vec<Converted>,
+}
+
+impl RuleSink for ExampleRules<'_> {
+    fn add_rule(&mut self, rule: PropExpr) {
+        self.rules.push(Converted::new(rule, self.opts));
+    }
+
+    fn enforcer(&mut self, name: &'static str) {
+        self.enforcer = name;
+    }
+}
+
+/// 生成每个例子与每个后缀树节点的规则并加入求解器；出错时不再处理之后的例子或节点
+///
+/// `LearnOptions::encode_jobs` 为 `1` 时逐条生成并加入，不保存规则。否则在独立的线程池中每批并行生成
+/// `encode_jobs` 个例子或节点的规则并转换为 CNF，再按顺序加入求解器，因此结果与依次生成相同，
+/// 同时保存的规则也只限于一批。
+fn add_examples<W: Write>(ctx: &Context, sink: &mut ConverterSink<W>) -> Result<(), LearnError> {
+    let examples: Vec<_> = ctx.examples().collect();
+    let nodes = ctx.trie().map_or(0, |trie| trie.len());
+    let opts = sink.opts;
+    if opts.encode_jobs <= 1 {
+        for e in examples.into_iter() {
+            ContextEnforcer.generate_example(ctx, e, sink);
+            sink.take_error()?;
+        }
+        for n in 0..nodes {
+            ContextEnforcer.generate_trie_node(ctx, n, sink);
+            sink.take_error()?;
+        }
+        return Ok(());
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opts.encode_jobs)
+        .build()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    let parts = examples.len() + nodes;
+    for start in (0..parts).step_by(opts.encode_jobs) {
+        let batch: Vec<ExampleRules> = pool.install(|| {
+            (start..parts.min(start + opts.encode_jobs))
+                .into_par_iter()
+                .map(|i| {
+                    let mut rules = ExampleRules {
+                        opts,
+                        enforcer: "",
+                        rules: vec![],
+                    };
+                    match examples.get(i) {
+                        Some(e) => ContextEnforcer.generate_example(ctx, e, &mut rules),
+                        None => {
+                            ContextEnforcer.generate_trie_node(ctx, i - examples.len(), &mut rules)
+                        }
+                    }
+                    rules
+                })
+                .collect()
+        });
+        for rules in batch.into_iter() {
+            sink.enforcer(rules.enforcer);
+            for rule in rules.rules.into_iter() {
+                sink.add_converted(rule);
+            }
+        }
+        sink.take_error()?;
+    }
+    Ok(())
+}
+
+fn solve_iter(
+    ctx: &Context,
+    conv: &mut SATConverter,
+    opts: &LearnOptions,
+    output: &mut impl Write,
+    stats: &mut BoundStats,
+) -> Result<Option<Solution>, LearnError> {
+    // 逐条生成规则并加入求解器，只在当前尺寸限制下成立的规则由 `Bound(n)` 激活
+    let start = Instant::now();
+    let mut sink = ConverterSink {
+        conv,
+        opts,
+        output,
+        stats,
+        enforcer: "",
+        bound: None,
+        error: None,
+    };
+    ContextEnforcer.generate_structure(ctx, &mut sink);
+    sink.take_error()?;
+    add_examples(ctx, &mut sink)?;
+    ContextEnforcer.generate_global(ctx, &mut sink);
+    sink.take_error()?;
+    sink.bound = Some(Variable::Bound(ctx.max_skeletons()));
+    ContextEnforcer.generate_bound(ctx, &mut sink);
+    sink.take_error()?;
+    stats.add_encode_time(start.elapsed());
+
+    if opts.dump == Some(Dump::Cnf) {
+        writeln!(output, "c n = {}", ctx.max_skeletons())?;
+        conv.write_dimacs(output, &assumptions(ctx))?;
+    }
+
+    solve_model(ctx, conv, opts, output, stats)
+}
+
+/// 对已经添加的规则求解并构造公式
+fn solve_model(
+    ctx: &Context,
+    conv: &mut SATConverter,
+    opts: &LearnOptions,
+    output: &mut impl Write,
+    stats: &mut BoundStats,
+) -> Result<Option<Solution>, LearnError> {
+    // 求解
+    let before = conv.solver_stats();
+    let start = Instant::now();
+    let result = conv.solve(&assumptions(ctx));
+    stats.add_solve(start.elapsed(), before, conv.solver_stats());
+    let result = result?;
+    stats.satisfiable = Some(stats.satisfiable == Some(true) || result.is_some());
+    stats.max_errors = ctx.max_errors();
+    stats.variables = conv.var_count();
+    stats.clauses = conv.clause_count();
+
+    if opts.dump == Some(Dump::Result) {
+        writeln!(
+            output,
+            "n = {}, SAT = {}",
+            ctx.max_skeletons(),
+            result.is_some()
+        )?;
+        for word in ctx.words() {
+            writeln!(output, "word {} => {}", word.0, word.1)?;
+        }
+        if let Some(ref pos_vars) = result {
+            for v in pos_vars.iter().filter_map(|v| match v {
+                Variable::Run(_, _, _) | Variable::FlipRun(_, _, _) => None,
+                Variable::TrieRun(_, _) => None,
+                Variable::Phantom(_) => None,
+                Variable::Exactly(_) => None,
+                Variable::Bound(_) => None,
+                Variable::Counter(_, _) => None,
+                s => Some(s),
+            }) {
+                writeln!(output, "{:?} = true", v)?;
+            }
+        }
+    }
+
+    if opts.verbose {
+        eprintln!(
+            "SAT 求解{}，n = {:?}{}，变量数 = {}，子句数 = {}",
+            if result.is_some() {
+                "可满足"
+            } else {
+                "不可满足"
+            },
+            ctx.max_skeletons(),
+            match ctx.max_errors() {
+                Some(k) => format!("，不被满足的例子代价至多为 {}", k),
+                None => String::new(),
+            },
+            conv.var_count(),
+            conv.clause_count()
+        );
+    }
+
+    // 生成语法树
+    match result {
+        Some(pos_vars) => {
+            let model = ltl::Model::new(ctx, &pos_vars);
+            Ok(Some(Solution {
+                formula: model.make_ltl(0)?,
+                structure: model.structure(0)?,
+                relaxed: pos_vars
+                    .iter()
+                    .filter_map(|v| match v {
+                        Variable::Relax(e) => Some(*e),
+                        _ => None,
+                    })
+                    .collect(),
+            }))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::verify;
+
+    #[test]
+    fn and_at_last_step() {
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p", "q"]], [["q"], ["p", "q"]]],
+            "traces_neg": [[["p"]], [["q"]], [["p", "q"], ["p"]]]
+        }"#;
+        let result = learn(Input::try_from(json).unwrap(), LearnOptions::default()).unwrap();
+        assert_eq!(result.size, 5);
+        let verification = crate::verify(&Input::try_from(json).unwrap(), &result.formula);
+        assert_eq!(verification.accepted_pos, vec![0, 1]);
+        assert_eq!(verification.rejected_neg, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn all_solutions() {
+        let json = r#"{"vocab": ["p", "q"], "traces_pos": [[["p", "q"]]], "traces_neg": [[[]]]}"#;
+        let opts = LearnOptions {
+            max_solutions: usize::MAX,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        let input = Input::try_from(json).unwrap();
+        let mut formulas = vec![&result.formula];
+        for formula in result.alternatives.iter() {
+            assert!(!formulas.contains(&formula));
+            formulas.push(formula);
+        }
+        // p、q、F p、F q、G p、G q
+        assert_eq!(formulas.len(), 6);
+        for formula in formulas {
+            assert!(crate::verify(&input, formula).is_consistent());
+        }
+
+        // `!` 作用于字面量与相反的字面量是同一公式，只输出一次
+        let json = r#"{"vocab": ["p"], "traces_pos": [[[]]], "traces_neg": [[["p"]]]}"#;
+        let opts = LearnOptions {
+            max_size: 3,
+            operators: Some(vec![Operator::Not]),
+            max_solutions: usize::MAX,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        assert_eq!(result.formula, LtlNode::Literal(false, "p".to_string()));
+        assert!(result.alternatives.is_empty());
+    }
+
+    #[test]
+    fn noise_tolerant() {
+        // 最后一个反例与第一个正例相同
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["q"]], [["p"], ["p"], ["q"]]],
+            "traces_neg": [[["p"]], [["q"], ["p"]], [["p"], ["q"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 4,
+            ..LearnOptions::default()
+        };
+        match learn(Input::try_from(json).unwrap(), opts.clone()) {
+            Err(LearnError::Input(InputError::Contradictory(contradictions))) => assert_eq!(
+                contradictions,
+                vec![crate::Contradiction {
+                    pos: vec![0],
+                    neg: vec![2],
+                    hard: false
+                }]
+            ),
+            result => panic!("应当报告矛盾的例子：{:?}", result),
+        }
+        let opts = LearnOptions {
+            noise_tolerant: true,
+            ..opts
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        let verification = crate::verify(&Input::try_from(json).unwrap(), &result.formula);
+        assert_eq!(result.sacrificed_pos, verification.rejected_pos);
+        assert_eq!(result.sacrificed_neg, verification.accepted_neg);
+        assert_eq!(result.sacrificed_pos.len() + result.sacrificed_neg.len(), 1);
+    }
+
+    #[test]
+    fn weighted() {
+        // 相同的正例与反例中，权重较小的被放弃
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [{"trace": [["p"], ["q"]], "weight": 2}, [["p"], ["p"], ["q"]]],
+            "traces_neg": [[["p"]], [["q"], ["p"]], [["p"], ["q"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 4,
+            noise_tolerant: true,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts.clone()).unwrap();
+        assert_eq!(result.sacrificed_neg, vec![2]);
+        assert_eq!(result.sacrificed_cost, 1);
+
+        // 相互矛盾的例子都必须被满足，即使允许例子不被满足也报告矛盾
+        let json = json.replace(r#""weight": 2"#, r#""hard": true"#).replace(
+            r#"[["p"], ["q"]]]"#,
+            r#"{"trace": [["p"], ["q"]], "hard": true}]"#,
+        );
+        match learn(Input::try_from(json.as_str()).unwrap(), opts) {
+            Err(LearnError::Input(InputError::Contradictory(contradictions))) => assert_eq!(
+                contradictions,
+                vec![crate::Contradiction {
+                    pos: vec![0],
+                    neg: vec![2],
+                    hard: true
+                }]
+            ),
+            result => panic!("应当报告矛盾的例子：{:?}", result),
+        }
+    }
+
+    #[test]
+    fn relaxed_hard_examples() {
+        // 必须满足的例子即使对应的 Relax 变量为真也不能被放弃
+        let check_all = |json: &str| {
+            let ctx = Context::try_from(Input::try_from(json).unwrap()).unwrap();
+            let solution = Solution {
+                formula: LtlNode::Literal(true, "p".to_string()),
+                structure: vec![],
+                relaxed: ctx.examples().map(|ex| ex.id()).collect(),
+            };
+            check(&ctx, &solution)
+        };
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [{"trace": [["p"]], "hard": true}, [["q"]]],
+            "traces_neg": [{"trace": [["p"], ["q"]], "weight": 3}]
+        }"#;
+        assert_eq!(check_all(json).unwrap(), (vec![1], vec![0], 4));
+
+        let json = json.replace(
+            r#""weight": 3}]"#,
+            r#""weight": 3}, {"trace": [["p"], ["p"]], "hard": true}]"#,
+        );
+        match check_all(&json) {
+            Err(LearnError::Mismatch {
+                positive, trace, ..
+            }) => assert_eq!((positive, trace), (false, 1)),
+            result => panic!("必须满足的反例不应被放弃：{:?}", result),
+        }
+    }
+
+    #[test]
+    fn past_time() {
+        // 每个 g 的前一个时间点都有 r
+        let json = r#"{
+            "vocab": ["g", "r"],
+            "traces_pos": [[["r"], ["g"]], [[], ["r"], ["g"]], [[]], [["r"], ["g", "r"], ["g"]]],
+            "traces_neg": [[["g"]], [[], ["g"]], [["g"], ["r"]], [["r"], [], ["g"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 5,
+            past_time: true,
+            max_solutions: usize::MAX,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        let expected: LtlNode = "G (!g | Y r)".parse().unwrap();
+        assert!(result.formula == expected || result.alternatives.contains(&expected));
+    }
+
+    /// 公式中出现的运算符
+    fn operators(f: &LtlNode, ops: &mut HashSet<Operator>) {
+        let (op, children): (Option<Operator>, Vec<&LtlNode>) = match f {
+            LtlNode::And(l, r) => (Some(Operator::And), vec![l, r]),
+            LtlNode::Or(l, r) => (Some(Operator::Or), vec![l, r]),
+            LtlNode::Until(l, r) => (Some(Operator::Until), vec![l, r]),
+            LtlNode::Release(l, r) => (Some(Operator::Release), vec![l, r]),
+            LtlNode::Since(l, r) => (Some(Operator::Since), vec![l, r]),
+            LtlNode::Trigger(l, r) => (Some(Operator::Trigger), vec![l, r]),
+            LtlNode::Next(l) => (Some(Operator::Next), vec![l]),
+            LtlNode::WNext(l) => (Some(Operator::WNext), vec![l]),
+            LtlNode::Eventually(l) => (Some(Operator::Eventually), vec![l]),
+            LtlNode::Always(l) => (Some(Operator::Always), vec![l]),
+            LtlNode::Yesterday(l) => (Some(Operator::Yesterday), vec![l]),
+            LtlNode::WYesterday(l) => (Some(Operator::WYesterday), vec![l]),
+            LtlNode::Once(l) => (Some(Operator::Once), vec![l]),
+            LtlNode::Historically(l) => (Some(Operator::Historically), vec![l]),
+            LtlNode::Not(l) => (Some(Operator::Not), vec![l]),
+            LtlNode::Implies(l, r) => (Some(Operator::Implies), vec![l, r]),
+            LtlNode::True => (Some(Operator::True), vec![]),
+            LtlNode::False => (Some(Operator::False), vec![]),
+            LtlNode::Literal(_, _) => (None, vec![]),
+        };
+        ops.extend(op);
+        for c in children {
+            operators(c, ops);
+        }
+    }
+
+    #[test]
+    fn restricted_operators() {
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["p"], ["q"]], [["q"]], [["p"], ["q"], []]],
+            "traces_neg": [[["p"], [], ["q"]], [[]], [["p"], ["p"]]]
+        }"#;
+        let allowed = vec![
+            Operator::Always,
+            Operator::Eventually,
+            Operator::Next,
+            Operator::And,
+            Operator::Or,
+        ];
+        let opts = LearnOptions {
+            max_size: 7,
+            operators: Some(allowed.clone()),
+            max_solutions: usize::MAX,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        let mut used = HashSet::new();
+        for f in std::iter::once(&result.formula).chain(result.alternatives.iter()) {
+            operators(f, &mut used);
+        }
+        assert!(used.iter().all(|op| allowed.contains(op)), "{:?}", used);
+
+        let opts = LearnOptions {
+            max_size: 7,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        assert_eq!(result.formula, "p U q".parse().unwrap());
+    }
+
+    #[test]
+    fn negation_and_implication() {
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"]], [[], []], [["p"], ["q"]]],
+            "traces_neg": [[[], ["p"]], [["p"], ["p", "q"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 4,
+            operators: Some(vec![Operator::Next, Operator::Not]),
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        assert_eq!(
+            result.formula,
+            LtlNode::Not(Box::new("X p".parse().unwrap()))
+        );
+
+        let json = r#"{
+            "vocab": ["req", "grant"],
+            "traces_pos": [[["req"], [], ["grant"]], [[], []], [["req", "grant"]], [["grant"], ["req"], ["grant"]]],
+            "traces_neg": [[["req"]], [["grant"], ["req"]], [[], ["req"], []]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 5,
+            operators: Some(vec![
+                Operator::Always,
+                Operator::Eventually,
+                Operator::Implies,
+                Operator::True,
+            ]),
+            max_solutions: usize::MAX,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        let expected: LtlNode = "G (req -> F grant)".parse().unwrap();
+        assert!(result.formula == expected || result.alternatives.contains(&expected));
+    }
+
+    #[test]
+    fn lasso() {
+        // 有穷序列上 `G F p` 与 `F p` 无法区分，套索序列上可以
+        let json = r#"{
+            "vocab": ["p"],
+            "traces_pos": [{"trace": [[], ["p"], []], "loop": 1}, {"trace": [[], [], ["p"]], "loop": 0}],
+            "traces_neg": [{"trace": [["p"], []], "loop": 1}, [["p"], []], {"trace": [[], ["p"], []], "loop": 2}]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 4,
+            ..LearnOptions::default()
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts.clone()).unwrap();
+        assert_eq!(result.size, 3);
+        let input = Input::try_from(json).unwrap();
+        assert!(verify(&input, &result.formula).is_consistent());
+        assert!(verify(&input, &"G F p".parse().unwrap()).is_consistent());
+
+        let opts = LearnOptions {
+            past_time: true,
+            ..opts
+        };
+        assert!(matches!(
+            learn(Input::try_from(json).unwrap(), opts),
+            Err(LearnError::Input(InputError::PastTimeLasso {
+                positive: true,
+                trace: 0
+            }))
+        ));
+    }
+
+    #[test]
+    fn timeout() {
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["p"], ["q"]], [["q"]], [["p"], ["q"], []]],
+            "traces_neg": [[["p"], [], ["q"]], [[]], [["p"], ["p"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 4,
+            timeout: Some(Duration::from_secs(0)),
+            ..LearnOptions::default()
+        };
+        assert!(matches!(
+            learn(Input::try_from(json).unwrap(), opts.clone()),
+            Err(LearnError::Timeout {
+                reason: StopReason::Timeout,
+                bound: 2,
+                unsat: None
+            })
+        ));
+
+        let opts = LearnOptions {
+            timeout: Some(Duration::from_secs(600)),
+            bound_timeout: Some(Duration::from_secs(0)),
+            ..opts
+        };
+        assert!(matches!(
+            learn(Input::try_from(json).unwrap(), opts.clone()),
+            Err(LearnError::Timeout {
+                reason: StopReason::BoundTimeout,
+                bound: 2,
+                unsat: None
+            })
+        ));
+
+        // 时限足够时结果不变
+        let opts = LearnOptions {
+            bound_timeout: Some(Duration::from_secs(600)),
+            ..opts
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        assert_eq!(result.formula, "p U q".parse().unwrap());
+    }
+
+    #[test]
+    fn parallel() {
+        let inputs = [
+            r#"{
+                "vocab": ["p", "q"],
+                "traces_pos": [[["p"], ["p"], ["q"]], [["q"]], [["p"], ["q"], []]],
+                "traces_neg": [[["p"], [], ["q"]], [[]], [["p"], ["p"]]]
+            }"#,
+            r#"{
+                "vocab": ["p", "q"],
+                "traces_pos": [{"trace": [["p"], ["q"]], "weight": 2}, [["p"], ["p"], ["q"]]],
+                "traces_neg": [[["p"]], [["q"], ["p"]], [["p"], ["q"]]]
+            }"#,
+        ];
+        for json in inputs.iter() {
+            for &noise_tolerant in [false, true].iter() {
+                let opts = LearnOptions {
+                    max_size: 5,
+                    noise_tolerant,
+                    max_solutions: usize::MAX,
+                    ..LearnOptions::default()
+                };
+                let sequential = learn(Input::try_from(*json).unwrap(), opts.clone());
+                let opts = LearnOptions { jobs: 4, ..opts };
+                let parallel = learn(Input::try_from(*json).unwrap(), opts);
+                match (sequential, parallel) {
+                    (Ok(sequential), Ok(parallel)) => assert_eq!(sequential, parallel),
+                    (Err(LearnError::Unsatisfiable(a)), Err(LearnError::Unsatisfiable(b))) => {
+                        assert_eq!(a, b)
+                    }
+                    (
+                        Err(LearnError::Input(InputError::Contradictory(a))),
+                        Err(LearnError::Input(InputError::Contradictory(b))),
+                    ) => assert_eq!(a, b),
+                    (sequential, parallel) => panic!("{:?} != {:?}", sequential, parallel),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn preprocess() {
+        // 第二个正例与第一个正例重复，最后一个反例与第一个正例相同
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["q"]], [["p"], ["q"]], [["p"], ["p"], ["q"]], [["q"], ["q"]]],
+            "traces_neg": [[["p"]], [["q"], ["p"]], [["q"], ["p"], ["p"]], [["p"], ["q"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 4,
+            noise_tolerant: true,
+            ..LearnOptions::default()
+        };
+        let expected = learn(Input::try_from(json).unwrap(), opts.clone()).unwrap();
+        let opts = LearnOptions {
+            dedup: true,
+            share_suffixes: true,
+            ..opts
+        };
+        let result = learn(Input::try_from(json).unwrap(), opts).unwrap();
+        assert_eq!(result.size, expected.size);
+        assert_eq!(result.sacrificed_cost, expected.sacrificed_cost);
+        let verification = crate::verify(&Input::try_from(json).unwrap(), &result.formula);
+        assert_eq!(result.sacrificed_pos, verification.rejected_pos);
+        assert_eq!(result.sacrificed_neg, verification.accepted_neg);
+    }
+
+    #[test]
+    fn trie_encoding() {
+        // 最后一个正例为套索序列，依然逐个例子编码
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["q"]], [["q"], ["p"], ["q"]], [["p", "q"]],
+                           {"trace": [["p"], ["q"]], "loop": 1}],
+            "traces_neg": [[["q"], ["p"]], [["p"], ["p"], ["q"]], [["p"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 5,
+            noise_tolerant: true,
+            operators: Some(vec![
+                Operator::Next,
+                Operator::Eventually,
+                Operator::Until,
+                Operator::And,
+                Operator::Not,
+                Operator::Implies,
+            ]),
+            ..LearnOptions::default()
+        };
+        let expected = learn(Input::try_from(json).unwrap(), opts.clone()).unwrap();
+        let opts = LearnOptions {
+            encoding: Encoding::Trie,
+            ..opts
+        };
+        let mut stats = vec![];
+        let result = learn_with_stats(
+            Input::try_from(json).unwrap(),
+            opts,
+            &mut vec![],
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(result.size, expected.size);
+        assert_eq!(result.sacrificed_cost, expected.sacrificed_cost);
+        let verification = crate::verify(&Input::try_from(json).unwrap(), &result.formula);
+        assert_eq!(result.sacrificed_pos, verification.rejected_pos);
+        assert_eq!(result.sacrificed_neg, verification.accepted_neg);
+        let enforcers: Vec<_> = stats[0].rules.iter().map(|r| r.enforcer).collect();
+        assert!(enforcers.contains(&"TrieEnforcer") && enforcers.contains(&"ExampleEnforcer"));
+        assert_eq!("trie".parse::<Encoding>(), Ok(Encoding::Trie));
+        assert!("suffix".parse::<Encoding>().is_err());
+    }
+
+    #[test]
+    fn trie_encoding_past_time() {
+        let json = r#"{"vocab": ["p"], "traces_pos": [[["p"]]], "traces_neg": [[[]]]}"#;
+        let opts = LearnOptions {
+            encoding: Encoding::Trie,
+            past_time: true,
+            ..LearnOptions::default()
+        };
+        assert!(matches!(
+            learn(Input::try_from(json).unwrap(), opts),
+            Err(LearnError::Input(InputError::PastTimeTrie))
+        ));
+        let opts = LearnOptions {
+            encoding: Encoding::Trie,
+            operators: Some(vec![Operator::Once]),
+            ..LearnOptions::default()
+        };
+        assert!(matches!(
+            learn(Input::try_from(json).unwrap(), opts),
+            Err(LearnError::Input(InputError::PastTimeTrie))
+        ));
+    }
+
+    #[test]
+    fn parallel_encoding() {
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["p"], ["q"]], [["q"]], [["p"], ["q"], []], [["p", "q"]]],
+            "traces_neg": [[["p"], [], ["q"]], [[]], [["p"], ["p"]], [["q"], ["p"]]]
+        }"#;
+        let dump = |encode_jobs, incremental, tseitin, encoding| {
+            let opts = LearnOptions {
+                max_size: 4,
+                encode_jobs,
+                incremental,
+                tseitin,
+                encoding,
+                dump: Some(Dump::Cnf),
+                ..LearnOptions::default()
+            };
+            let mut output = vec![];
+            // 只比较生成的 CNF，不关心是否可满足
+            let _ = learn_with_output(Input::try_from(json).unwrap(), opts, &mut output);
+            String::from_utf8(output).unwrap()
+        };
+        for &incremental in [false, true].iter() {
+            for &tseitin in [false, true].iter() {
+                for &encoding in [Encoding::Example, Encoding::Trie].iter() {
+                    assert_eq!(
+                        dump(1, incremental, tseitin, encoding),
+                        dump(4, incremental, tseitin, encoding)
+                    );
+                }
+            }
+        }
+    }
+
+    /// 逐条加入求解器的规则与 `ContextEnforcer` 一次生成的规则顺序相同，并行生成例子的规则时也是如此
+    #[test]
+    fn streaming() {
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["p"], ["q"]], [["q"]], [["p"], ["q"], []]],
+            "traces_neg": [[["p"], [], ["q"]], [[]], [["p"], ["p"]]]
+        }"#;
+        for &(encode_jobs, trie) in [(1, false), (1, true), (4, false), (4, true)].iter() {
+            let opts = LearnOptions {
+                encode_jobs,
+                dump: Some(Dump::Expr),
+                ..LearnOptions::default()
+            };
+            let mut ctx = Context::try_from(Input::try_from(json).unwrap()).unwrap();
+            ctx.set_size_bound(3);
+            if trie {
+                ctx.build_trie();
+            }
+            let mut expected: Vec<String> = ContextEnforcer
+                .rules(&ctx)
+                .into_iter()
+                .map(|r| format!("{:?}", r))
+                .collect();
+            expected.extend(
+                ContextEnforcer
+                    .bound_rules(&ctx)
+                    .into_iter()
+                    .map(|r| format!("{:?}", Variable::Bound(3) >> r)),
+            );
+            let mut output = vec![];
+            let mut stats = BoundStats::default();
+            solve_iter(
+                &ctx,
+                &mut new_converter(&opts),
+                &opts,
+                &mut output,
+                &mut stats,
+            )
+            .unwrap();
+            let output = String::from_utf8(output).unwrap();
+            let streamed: Vec<_> = output.lines().take(expected.len()).collect();
+            assert_eq!(streamed, expected);
+            let total: usize = stats.rules.iter().map(|r| r.rules).sum();
+            assert_eq!(total, expected.len());
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let json = r#"{
+            "vocab": ["p", "q"],
+            "traces_pos": [[["p"], ["p"], ["q"]], [["q"]], [["p"], ["q"], []]],
+            "traces_neg": [[["p"], [], ["q"]], [[]], [["p"], ["p"]]]
+        }"#;
+        let opts = LearnOptions {
+            max_size: 4,
+            ..LearnOptions::default()
+        };
+        let mut stats = vec![];
+        let result = learn_with_stats(
+            Input::try_from(json).unwrap(),
+            opts.clone(),
+            &mut std::io::sink(),
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(result.size, 3);
+        let bounds: Vec<_> = stats.iter().map(|s| (s.bound, s.satisfiable)).collect();
+        assert_eq!(bounds, vec![(2, Some(false)), (3, Some(true))]);
+        for s in stats.iter() {
+            let examples = s.rules.iter().find(|r| r.enforcer == "ExampleEnforcer");
+            assert!(examples.unwrap().rules > 0);
+            assert!(s.variables > 0 && s.clauses > 0 && s.solves == 1);
+            assert!(s.conflicts.is_some());
+        }
+
+        // 并行求解时按尺寸限制的顺序记录相同的统计
+        let mut parallel = vec![];
+        let opts = LearnOptions { jobs: 3, ..opts };
+        learn_with_stats(
+            Input::try_from(json).unwrap(),
+            opts.clone(),
+            &mut std::io::sink(),
+            &mut parallel,
+        )
+        .unwrap();
+        let counts = |stats: &[BoundStats]| -> Vec<_> {
+            stats
+                .iter()
+                .map(|s| (s.bound, s.rules.clone(), s.variables, s.clauses))
+                .collect()
+        };
+        assert_eq!(counts(&stats), counts(&parallel));
+
+        // 超时时也记录已经求解的尺寸限制
+        let mut stats = vec![];
+        let opts = LearnOptions {
+            jobs: 1,
+            timeout: Some(Duration::from_secs(0)),
+            ..opts
+        };
+        let result = learn_with_stats(
+            Input::try_from(json).unwrap(),
+            opts,
+            &mut std::io::sink(),
+            &mut stats,
+        );
+        assert!(matches!(result, Err(LearnError::Timeout { .. })));
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].satisfiable, None);
+    }
+}
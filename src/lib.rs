@@ -0,0 +1,42 @@
+//! 根据输入序列学习 LTL 公式
+//!
+//! 基于 https://aaai.org/ojs/index.php/ICAPS/article/view/3529
+//!
+//! # Example
+//!
+//! ```
+//! use limeltl::{learn, Input, LearnOptions};
+//! use std::convert::TryFrom;
+//!
+//! let input = Input::try_from(
+//!     r#"{
+//!         "vocab": ["p", "q"],
+//!         "traces_pos": [[["p"], ["q"]], [["q"]]],
+//!         "traces_neg": [[["p"]], [["p"], ["p"]]]
+//!     }"#,
+//! )
+//! .unwrap();
+//! let result = learn(input, LearnOptions::default()).unwrap();
+//! println!("{}", result.formula);
+//! ```
+
+mod bool_logic;
+mod context;
+mod enforcers;
+mod learn;
+mod ltl;
+// mod maple;
+mod sat;
+mod stats;
+mod utils;
+mod verify;
+
+pub use context::{Context, Contradiction, Input, InputError, UnknownWord, Weight};
+pub use learn::{
+    learn, learn_with_output, learn_with_stats, Dump, Encoding, LearnError, LearnOptions,
+    LearnResult, StopReason, UnknownEncoding,
+};
+pub use ltl::{DecodeError, LtlNode, Operator, ParseError, TupleLtlNode, UnknownOperator};
+pub use sat::{Backend, EncodingError, SolverError, UnknownBackend};
+pub use stats::{BoundStats, RuleCount};
+pub use verify::{verify, Verification};
@@ -0,0 +1,190 @@
+//! 有穷序列上的 LTLf 语义
+//!
+//! 与 `enforcers::example` 中的编码一致：在最后一个时间点，强 `X` 为假而弱 `N` 为真；
+//! `U`、`R`、`F`、`G` 只考虑当前时间点之后的有穷后缀。过去时运算符与之对称：在第一个时间点，
+//! 强 `Y` 为假而弱 `Z` 为真；`S`、`T`、`O`、`H` 只考虑当前时间点之前的前缀。
+//!
+//! 套索序列（最后一个时间点的后继为循环起点）上使用无穷序列的 LTL 语义。过去时运算符仍然只考虑
+//! 序列本身的前缀，因此学习时不允许同时使用套索序列与过去时运算符。
+
+use super::LtlNode;
+use std::collections::HashSet;
+
+impl LtlNode {
+    /// 公式在有穷序列 `trace` 的时间 `t` 是否成立，见 `eval_lasso`
+    pub fn eval(&self, trace: &[HashSet<String>], t: usize) -> bool {
+        self.eval_lasso(trace, None, t)
+    }
+
+    /// 公式在序列 `trace` 的时间 `t` 是否成立，`lasso` 为套索序列的循环起点，
+    /// 要求 `lasso < trace.len()`；时间 `t` 不在序列中时（如空序列）任何公式都不成立
+    pub fn eval_lasso(&self, trace: &[HashSet<String>], lasso: Option<usize>, t: usize) -> bool {
+        if t >= trace.len() {
+            return false;
+        }
+        let last = trace.len() - 1;
+        let next = if t < last { Some(t + 1) } else { lasso };
+        // 从 t 出发依次经过的所有时间点
+        let mut reach = (t..=last).chain(match lasso {
+            Some(l) if l < t => l..t,
+            _ => 0..0,
+        });
+        let eval = |node: &LtlNode, k| node.eval_lasso(trace, lasso, k);
+        match self {
+            LtlNode::And(lhs, rhs) => eval(lhs, t) && eval(rhs, t),
+            LtlNode::Or(lhs, rhs) => eval(lhs, t) || eval(rhs, t),
+            LtlNode::Next(lhs) => next.is_some_and(|k| eval(lhs, k)),
+            LtlNode::WNext(lhs) => next.map_or(true, |k| eval(lhs, k)),
+            LtlNode::Until(lhs, rhs) => {
+                for k in reach {
+                    if eval(rhs, k) {
+                        return true;
+                    }
+                    if !eval(lhs, k) {
+                        return false;
+                    }
+                }
+                false
+            }
+            LtlNode::Release(lhs, rhs) => {
+                for k in reach {
+                    if !eval(rhs, k) {
+                        return false;
+                    }
+                    if eval(lhs, k) {
+                        return true;
+                    }
+                }
+                true
+            }
+            LtlNode::Eventually(lhs) => reach.any(|k| eval(lhs, k)),
+            LtlNode::Always(lhs) => reach.all(|k| eval(lhs, k)),
+            LtlNode::Yesterday(lhs) => t > 0 && eval(lhs, t - 1),
+            LtlNode::WYesterday(lhs) => t == 0 || eval(lhs, t - 1),
+            LtlNode::Since(lhs, rhs) => {
+                for k in (0..=t).rev() {
+                    if eval(rhs, k) {
+                        return true;
+                    }
+                    if !eval(lhs, k) {
+                        return false;
+                    }
+                }
+                false
+            }
+            LtlNode::Trigger(lhs, rhs) => {
+                for k in (0..=t).rev() {
+                    if !eval(rhs, k) {
+                        return false;
+                    }
+                    if eval(lhs, k) {
+                        return true;
+                    }
+                }
+                true
+            }
+            LtlNode::Once(lhs) => (0..=t).any(|k| eval(lhs, k)),
+            LtlNode::Historically(lhs) => (0..=t).all(|k| eval(lhs, k)),
+            LtlNode::Not(lhs) => !eval(lhs, t),
+            LtlNode::Implies(lhs, rhs) => !eval(lhs, t) || eval(rhs, t),
+            LtlNode::Literal(pos, name) => trace[t].contains(name) == *pos,
+            LtlNode::True => true,
+            LtlNode::False => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn trace(steps: &[&[&str]]) -> Vec<HashSet<String>> {
+        steps
+            .iter()
+            .map(|s| s.iter().map(|w| w.to_string()).collect())
+            .collect()
+    }
+
+    fn eval(formula: &str, trace: &[HashSet<String>]) -> bool {
+        formula.parse::<LtlNode>().unwrap().eval(trace, 0)
+    }
+
+    #[test]
+    fn next() {
+        let tr = trace(&[&["p"], &["q"]]);
+        assert!(eval("X q", &tr));
+        assert!(!eval("X X q", &tr));
+        assert!(eval("X N q", &tr));
+        assert!(eval("X N false", &tr));
+        assert!(!eval("N p", &tr));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(!eval("N p", &[]));
+        assert!(!eval("!p", &[]));
+        assert!(!eval("G true", &[]));
+    }
+
+    #[test]
+    fn until_release() {
+        let tr = trace(&[&["p"], &["p"], &["q"]]);
+        assert!(eval("p U q", &tr));
+        assert!(!eval("p U q", &trace(&[&["p"], &["p"]])));
+        assert!(!eval("p U q", &trace(&[&["p"], &[], &["q"]])));
+        assert!(eval("q R !q", &trace(&[&[], &[]])));
+        assert!(eval("p R !q", &trace(&[&[], &["p"], &["q"]])));
+        assert!(!eval("p R !q", &trace(&[&[], &[], &["q"]])));
+        assert!(eval("!(p U q)", &tr) != eval("p U q", &tr));
+    }
+
+    #[test]
+    fn eventually_always() {
+        let tr = trace(&[&["p"], &["p", "q"], &["p"]]);
+        assert!(eval("G p", &tr));
+        assert!(eval("F q", &tr));
+        assert!(!eval("F G q", &tr));
+        assert!(eval("F G !q", &tr));
+        assert!(!eval("X X F q", &tr));
+    }
+
+    #[test]
+    fn past() {
+        let tr = trace(&[&["p"], &["q"], &["r"]]);
+        let at = |formula: &str, t| formula.parse::<LtlNode>().unwrap().eval(&tr, t);
+        assert!(!at("Y true", 0));
+        assert!(at("Z false", 0));
+        assert!(at("Y Y p", 2));
+        assert!(at("q S r", 2));
+        assert!(at("r S p", 0));
+        assert!(!at("r S p", 2));
+        assert!(at("(q | r) S p", 2));
+        assert!(at("O p", 2));
+        assert!(!at("H !p", 2));
+        assert!(at("H !p", 0) == at("!p", 0));
+        assert!(at("!(p S q)", 1) != at("p S q", 1));
+        assert!(at("G (!r | O p)", 0));
+    }
+
+    #[test]
+    fn lasso() {
+        // p, (q, !q)^ω
+        let tr = trace(&[&["p"], &["q"], &[]]);
+        let at = |formula: &str| {
+            formula
+                .parse::<LtlNode>()
+                .unwrap()
+                .eval_lasso(&tr, Some(1), 0)
+        };
+        assert!(at("X X X q"));
+        assert!(at("G F q"));
+        assert!(!at("F G q"));
+        assert!(at("G (q -> X !q)"));
+        assert!(!at("X G (p | q)"));
+        assert!(at("p U q"));
+        assert!(!at("X (!p U p)"));
+        assert!(at("X (p R !p)"));
+        assert!(at("X N true"));
+        assert!(!at("X X X false | N false"));
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum LtlNode {
     /// `a ^ b`
     And(Box<LtlNode>, Box<LtlNode>),
@@ -16,8 +16,28 @@ pub enum LtlNode {
     Eventually(Box<LtlNode>),
     /// `G a`
     Always(Box<LtlNode>),
+    /// `Y a`
+    Yesterday(Box<LtlNode>),
+    /// `Z a`，弱 `Y`
+    WYesterday(Box<LtlNode>),
+    /// `a S b`
+    Since(Box<LtlNode>, Box<LtlNode>),
+    /// `a T b`，`S` 的对偶
+    Trigger(Box<LtlNode>, Box<LtlNode>),
+    /// `O a`
+    Once(Box<LtlNode>),
+    /// `H a`
+    Historically(Box<LtlNode>),
+    /// `!a`
+    Not(Box<LtlNode>),
+    /// `a -> b`
+    Implies(Box<LtlNode>, Box<LtlNode>),
     /// (`!`?) `p`
     Literal(bool, String),
+    /// `true`
+    True,
+    /// `false`
+    False,
 }
 
 impl std::fmt::Display for LtlNode {
@@ -31,6 +51,14 @@ impl std::fmt::Display for LtlNode {
             LtlNode::Release(lhs, rhs) => f.write_fmt(format_args!("({} R {})", lhs, rhs)),
             LtlNode::Eventually(lhs) => f.write_fmt(format_args!("(F {})", lhs)),
             LtlNode::Always(lhs) => f.write_fmt(format_args!("(G {})", lhs)),
+            LtlNode::Yesterday(lhs) => f.write_fmt(format_args!("(Y {})", lhs)),
+            LtlNode::WYesterday(lhs) => f.write_fmt(format_args!("(Z {})", lhs)),
+            LtlNode::Since(lhs, rhs) => f.write_fmt(format_args!("({} S {})", lhs, rhs)),
+            LtlNode::Trigger(lhs, rhs) => f.write_fmt(format_args!("({} T {})", lhs, rhs)),
+            LtlNode::Once(lhs) => f.write_fmt(format_args!("(O {})", lhs)),
+            LtlNode::Historically(lhs) => f.write_fmt(format_args!("(H {})", lhs)),
+            LtlNode::Not(lhs) => f.write_fmt(format_args!("(!{})", lhs)),
+            LtlNode::Implies(lhs, rhs) => f.write_fmt(format_args!("({} -> {})", lhs, rhs)),
             LtlNode::Literal(pos, name) => {
                 if *pos {
                     f.write_fmt(format_args!("({})", name))
@@ -38,6 +66,36 @@ impl std::fmt::Display for LtlNode {
                     f.write_fmt(format_args!("(!({}))", name))
                 }
             }
+            LtlNode::True => f.write_str("true"),
+            LtlNode::False => f.write_str("false"),
+        }
+    }
+}
+
+/// 公式的否定，利用对偶将否定下推到字面量（否定范式），但不展开子公式中的 `!` 与 `->`
+impl std::ops::Not for LtlNode {
+    type Output = LtlNode;
+    fn not(self) -> Self::Output {
+        match self {
+            LtlNode::And(lhs, rhs) => LtlNode::Or(Box::new(!*lhs), Box::new(!*rhs)),
+            LtlNode::Or(lhs, rhs) => LtlNode::And(Box::new(!*lhs), Box::new(!*rhs)),
+            LtlNode::Next(lhs) => LtlNode::WNext(Box::new(!*lhs)),
+            LtlNode::WNext(lhs) => LtlNode::Next(Box::new(!*lhs)),
+            LtlNode::Until(lhs, rhs) => LtlNode::Release(Box::new(!*lhs), Box::new(!*rhs)),
+            LtlNode::Release(lhs, rhs) => LtlNode::Until(Box::new(!*lhs), Box::new(!*rhs)),
+            LtlNode::Eventually(lhs) => LtlNode::Always(Box::new(!*lhs)),
+            LtlNode::Always(lhs) => LtlNode::Eventually(Box::new(!*lhs)),
+            LtlNode::Yesterday(lhs) => LtlNode::WYesterday(Box::new(!*lhs)),
+            LtlNode::WYesterday(lhs) => LtlNode::Yesterday(Box::new(!*lhs)),
+            LtlNode::Since(lhs, rhs) => LtlNode::Trigger(Box::new(!*lhs), Box::new(!*rhs)),
+            LtlNode::Trigger(lhs, rhs) => LtlNode::Since(Box::new(!*lhs), Box::new(!*rhs)),
+            LtlNode::Once(lhs) => LtlNode::Historically(Box::new(!*lhs)),
+            LtlNode::Historically(lhs) => LtlNode::Once(Box::new(!*lhs)),
+            LtlNode::Not(lhs) => *lhs,
+            LtlNode::Implies(lhs, rhs) => LtlNode::And(lhs, Box::new(!*rhs)),
+            LtlNode::Literal(pos, name) => LtlNode::Literal(!pos, name),
+            LtlNode::True => LtlNode::False,
+            LtlNode::False => LtlNode::True,
         }
     }
 }
@@ -3,8 +3,16 @@
 mod ltl_node;
 pub use ltl_node::LtlNode;
 
+mod eval;
+
+mod operator;
+pub use operator::{Operator, UnknownOperator};
+
+mod parser;
+pub use parser::ParseError;
+
 mod tuple;
 pub use tuple::TupleLtlNode;
 
 mod model;
-pub use model::Model;
+pub use model::{DecodeError, Model};
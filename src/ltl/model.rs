@@ -2,6 +2,32 @@ use super::LtlNode;
 use crate::bool_logic::Variable;
 use crate::context::Context;
 
+/// 无法从求解结果构造公式
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum DecodeError {
+    /// 节点 `.0` 缺少节点类型信息
+    MissingType(usize),
+    /// 节点 `.0` 缺少子树
+    MissingChild(usize),
+    /// 字面量节点 `.0` 缺少单词信息
+    MissingWord(usize),
+    /// 单词表中不存在编号为 `.0` 的单词
+    UnknownWord(usize),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::MissingType(s) => write!(f, "求解结果不正确，节点 {} 缺少节点类型信息", s),
+            DecodeError::MissingChild(s) => write!(f, "求解结果不正确，节点 {} 未找到子树", s),
+            DecodeError::MissingWord(s) => write!(f, "求解结果不正确，节点 {} 未找到字面量信息", s),
+            DecodeError::UnknownWord(w) => write!(f, "求解结果不正确，意外的变量 {}", w),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
 pub struct Model<'a, 'b> {
     ctx: &'a Context,
     pos_vars: &'b [Variable],
@@ -14,80 +40,120 @@ impl<'a, 'b> Model<'a, 'b> {
 }
 
 impl<'a, 'b> Model<'a, 'b> {
-    pub fn make_ltl(&self, id: usize) -> LtlNode {
+    pub fn make_ltl(&self, id: usize) -> Result<LtlNode, DecodeError> {
         make_ltl(self, id)
     }
-}
 
-pub fn make_ltl(model: &Model, id: usize) -> LtlNode {
-    let sk_type = model
-        .pos_vars
-        .iter()
-        .find(|v| (v.is_atom() || v.is_unary() || v.is_binary()) && v.skeleton_id() == id)
-        .expect("求解结果不正确，缺少节点类型信息");
-    let left = model
-        .pos_vars
-        .iter()
-        .filter_map(|v| match v {
-            Variable::LeftChild(s, s1) if *s == id => Some(*s1),
-            _ => None,
-        })
-        .next();
-    let right = model
-        .pos_vars
-        .iter()
-        .filter_map(|v| match v {
-            Variable::RightChild(s, s1) if *s == id => Some(*s1),
+    /// 以 `id` 为根的公式涉及的所有结构变量（节点类型、子树、单词），用于排除该公式
+    pub fn structure(&self, id: usize) -> Result<Vec<Variable>, DecodeError> {
+        let mut vars = vec![*self.sk_type(id)?];
+        if let Some(v) = self.word(id) {
+            vars.push(v);
+        }
+        for child in [self.left(id), self.right(id)].iter().flatten() {
+            vars.push(*child);
+            if let Variable::LeftChild(_, s) | Variable::RightChild(_, s) = child {
+                vars.append(&mut self.structure(*s)?);
+            }
+        }
+        Ok(vars)
+    }
+
+    fn sk_type(&self, id: usize) -> Result<&Variable, DecodeError> {
+        self.pos_vars
+            .iter()
+            .find(|v| (v.is_atom() || v.is_unary() || v.is_binary()) && v.skeleton_id() == id)
+            .ok_or(DecodeError::MissingType(id))
+    }
+
+    /// 节点 `id` 的左子树变量，仅当节点为一元或二元节点时存在
+    fn left(&self, id: usize) -> Option<Variable> {
+        match self.sk_type(id) {
+            Ok(v) if v.is_unary() || v.is_binary() => self
+                .pos_vars
+                .iter()
+                .find(|v| matches!(v, Variable::LeftChild(s, _) if *s == id))
+                .copied(),
             _ => None,
-        })
-        .next();
-    match sk_type {
-        Variable::And(_) => LtlNode::And(
-            Box::new(make_ltl(model, left.expect("未找到子树"))),
-            Box::new(make_ltl(model, right.expect("未找到子树"))),
-        ),
-        Variable::Or(_) => LtlNode::Or(
-            Box::new(make_ltl(model, left.expect("未找到子树"))),
-            Box::new(make_ltl(model, right.expect("未找到子树"))),
-        ),
-        Variable::Next(_) => LtlNode::Next(Box::new(make_ltl(model, left.expect("未找到子树")))),
-        Variable::WNext(_) => LtlNode::WNext(Box::new(make_ltl(model, left.expect("未找到子树")))),
-        Variable::Until(_) => LtlNode::Until(
-            Box::new(make_ltl(model, left.expect("未找到子树"))),
-            Box::new(make_ltl(model, right.expect("未找到子树"))),
-        ),
-        Variable::Release(_) => LtlNode::Release(
-            Box::new(make_ltl(model, left.expect("未找到子树"))),
-            Box::new(make_ltl(model, right.expect("未找到子树"))),
-        ),
-        Variable::Eventually(_) => {
-            LtlNode::Eventually(Box::new(make_ltl(model, left.expect("未找到子树"))))
         }
-        Variable::Always(_) => {
-            LtlNode::Always(Box::new(make_ltl(model, left.expect("未找到子树"))))
+    }
+
+    /// 节点 `id` 的右子树变量，仅当节点为二元节点时存在
+    fn right(&self, id: usize) -> Option<Variable> {
+        match self.sk_type(id) {
+            Ok(v) if v.is_binary() => self
+                .pos_vars
+                .iter()
+                .find(|v| matches!(v, Variable::RightChild(s, _) if *s == id))
+                .copied(),
+            _ => None,
         }
-        Variable::Literal(_) => {
-            let lit = model
+    }
+
+    /// 节点 `id` 的单词变量，仅当节点为字面量时存在
+    fn word(&self, id: usize) -> Option<Variable> {
+        match self.sk_type(id) {
+            Ok(Variable::Literal(_)) => self
                 .pos_vars
                 .iter()
-                .filter_map(|v| match v {
-                    Variable::Word(s, w, p) if *s == id => Some((*p, *w)),
-                    _ => None,
-                })
-                .next()
-                .expect("未找到字面量信息");
+                .find(|v| matches!(v, Variable::Word(s, _, _) if *s == id))
+                .copied(),
+            _ => None,
+        }
+    }
+}
+
+pub fn make_ltl(model: &Model, id: usize) -> Result<LtlNode, DecodeError> {
+    let sk_type = model.sk_type(id)?;
+    let child = |v: Option<Variable>| -> Result<Box<LtlNode>, DecodeError> {
+        match v {
+            Some(Variable::LeftChild(_, s)) | Some(Variable::RightChild(_, s)) => {
+                Ok(Box::new(make_ltl(model, s)?))
+            }
+            _ => Err(DecodeError::MissingChild(id)),
+        }
+    };
+    let (left, right) = (model.left(id), model.right(id));
+    Ok(match sk_type {
+        Variable::And(_) => LtlNode::And(child(left)?, child(right)?),
+        Variable::Or(_) => LtlNode::Or(child(left)?, child(right)?),
+        Variable::Next(_) => LtlNode::Next(child(left)?),
+        Variable::WNext(_) => LtlNode::WNext(child(left)?),
+        Variable::Until(_) => LtlNode::Until(child(left)?, child(right)?),
+        Variable::Release(_) => LtlNode::Release(child(left)?, child(right)?),
+        Variable::Eventually(_) => LtlNode::Eventually(child(left)?),
+        Variable::Always(_) => LtlNode::Always(child(left)?),
+        Variable::Yesterday(_) => LtlNode::Yesterday(child(left)?),
+        Variable::WYesterday(_) => LtlNode::WYesterday(child(left)?),
+        Variable::Since(_) => LtlNode::Since(child(left)?, child(right)?),
+        Variable::Trigger(_) => LtlNode::Trigger(child(left)?, child(right)?),
+        Variable::Once(_) => LtlNode::Once(child(left)?),
+        Variable::Historically(_) => LtlNode::Historically(child(left)?),
+        // 否定的字面量与相反的字面量是同一公式，输出也相同
+        Variable::Not(_) => match *child(left)? {
+            LtlNode::Literal(pos, name) => LtlNode::Literal(!pos, name),
+            node => LtlNode::Not(Box::new(node)),
+        },
+        Variable::Implies(_) => LtlNode::Implies(child(left)?, child(right)?),
+        Variable::True(_) => LtlNode::True,
+        Variable::False(_) => LtlNode::False,
+        Variable::Literal(_) => {
+            let (p, w) = match model.word(id) {
+                Some(Variable::Word(_, w, p)) => (p, w),
+                _ => return Err(DecodeError::MissingWord(id)),
+            };
             LtlNode::Literal(
-                lit.0,
+                p,
                 model
                     .ctx
                     .words()
                     .iter()
-                    .filter_map(|(k, v)| if *v == lit.1 { Some(k) } else { None })
+                    .filter_map(|(k, v)| if *v == w { Some(k) } else { None })
                     .next()
-                    .expect("意外的变量")
+                    .ok_or(DecodeError::UnknownWord(w))?
                     .clone(),
             )
         }
         _ => unreachable!(),
-    }
+    })
 }
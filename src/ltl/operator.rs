@@ -0,0 +1,141 @@
+//! 编码中可以使用的运算符
+
+/// 可以出现在公式中的运算符，字面量总是可用
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Operator {
+    And,
+    Or,
+    Until,
+    Release,
+    Eventually,
+    Next,
+    WNext,
+    Always,
+    Since,
+    Trigger,
+    Once,
+    Historically,
+    Yesterday,
+    WYesterday,
+    Not,
+    Implies,
+    True,
+    False,
+}
+
+impl Operator {
+    /// 默认使用的将来时运算符
+    pub const FUTURE: &'static [Operator] = &[
+        Operator::And,
+        Operator::Or,
+        Operator::Until,
+        Operator::Release,
+        Operator::Eventually,
+        Operator::Next,
+        Operator::WNext,
+        Operator::Always,
+    ];
+
+    /// 过去时运算符
+    pub const PAST: &'static [Operator] = &[
+        Operator::Since,
+        Operator::Trigger,
+        Operator::Once,
+        Operator::Historically,
+        Operator::Yesterday,
+        Operator::WYesterday,
+    ];
+
+    /// 默认不使用的否定、蕴含与常量
+    pub const EXTRA: &'static [Operator] = &[
+        Operator::Not,
+        Operator::Implies,
+        Operator::True,
+        Operator::False,
+    ];
+
+    /// 运算符在中缀语法中的符号
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::And => "&",
+            Operator::Or => "|",
+            Operator::Until => "U",
+            Operator::Release => "R",
+            Operator::Eventually => "F",
+            Operator::Next => "X",
+            Operator::WNext => "N",
+            Operator::Always => "G",
+            Operator::Since => "S",
+            Operator::Trigger => "T",
+            Operator::Once => "O",
+            Operator::Historically => "H",
+            Operator::Yesterday => "Y",
+            Operator::WYesterday => "Z",
+            Operator::Not => "!",
+            Operator::Implies => "->",
+            Operator::True => "true",
+            Operator::False => "false",
+        }
+    }
+}
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// 未知的运算符符号
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UnknownOperator(pub String);
+
+impl std::fmt::Display for UnknownOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "未知的运算符 {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownOperator {}
+
+impl std::str::FromStr for Operator {
+    type Err = UnknownOperator;
+
+    /// 接受中缀语法中的符号，`&&` 与 `||` 分别视为 `&` 与 `|`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = match s {
+            "&&" => "&",
+            "||" => "|",
+            s => s,
+        };
+        Operator::FUTURE
+            .iter()
+            .chain(Operator::PAST.iter())
+            .chain(Operator::EXTRA.iter())
+            .find(|op| op.symbol() == s)
+            .copied()
+            .ok_or_else(|| UnknownOperator(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for op in Operator::FUTURE
+            .iter()
+            .chain(Operator::PAST.iter())
+            .chain(Operator::EXTRA.iter())
+        {
+            assert_eq!(op.to_string().parse::<Operator>(), Ok(*op));
+        }
+        assert_eq!("&&".parse::<Operator>(), Ok(Operator::And));
+        assert_eq!(" G".parse::<Operator>(), Ok(Operator::Always));
+        assert_eq!(
+            "W".parse::<Operator>(),
+            Err(UnknownOperator("W".to_string()))
+        );
+    }
+}
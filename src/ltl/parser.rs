@@ -0,0 +1,464 @@
+//! 从文本读取 LTL 公式
+//!
+//! 支持两种语法：
+//!
+//! - 中缀语法，即 `LtlNode` 的 `Display` 输出，如 `(p U (X (!(q))))`；同时接受省略多余括号、
+//!   `&&`、`||`、`!p`、`true`、`false` 等常见写法。优先级从高到低依次为一元运算符
+//!   （`!`、`X`、`N`、`F`、`G`、`Y`、`Z`、`O`、`H`）、`U`、`R`、`S` 与 `T`（右结合）、
+//!   `&`、`|`（左结合）、`->`（右结合）；
+//! - 元组语法，即 `TupleLtlNode` 的输出，如 `('&', 'p', ('X', ('!', 'q')))`。
+//!
+//! 中缀语法中单词由字母、数字与下划线组成，运算符与 `true`、`false` 为保留字。
+//! 否定将通过对偶下推到字面量。
+
+use super::LtlNode;
+
+/// 中缀语法中的二目时序运算符
+const BINARY: &[&str] = &["U", "R", "S", "T"];
+
+/// 无法解析公式
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ParseError {
+    /// 在位置 `.0` 遇到意外的内容 `.1`
+    Unexpected(usize, String),
+    /// 输入意外结束
+    UnexpectedEnd,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Unexpected(pos, s) => {
+                write!(f, "无法解析公式，位置 {} 处意外的 {:?}", pos, s)
+            }
+            ParseError::UnexpectedEnd => write!(f, "无法解析公式，输入意外结束"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+enum Token {
+    /// `(`、`)`、`,`、`!`、`&`、`|`
+    Punct(char),
+    /// `->`
+    Arrow,
+    /// 单词或保留字
+    Ident(String),
+    /// 带引号的字符串
+    Str(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Punct(c) => write!(f, "{}", c),
+            Token::Arrow => f.write_str("->"),
+            Token::Ident(s) => f.write_str(s),
+            Token::Str(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' | ')' | ',' | '!' => Token::Punct(c),
+            '&' | '|' => {
+                // `&&` 与 `||` 等同于 `&` 与 `|`
+                if chars.peek().map(|(_, n)| *n) == Some(c) {
+                    chars.next();
+                }
+                Token::Punct(c)
+            }
+            '-' if chars.peek().map(|(_, n)| *n) == Some('>') => {
+                chars.next();
+                Token::Arrow
+            }
+            '\'' | '"' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, n)) if n == c => break,
+                        Some((_, n)) => word.push(n),
+                        None => return Err(ParseError::UnexpectedEnd),
+                    }
+                }
+                Token::Str(word)
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some((_, n)) = chars.peek() {
+                    if n.is_alphanumeric() || *n == '_' {
+                        word.push(*n);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Ident(word)
+            }
+            c => return Err(ParseError::Unexpected(pos, c.to_string())),
+        };
+        tokens.push((pos, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(s: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            tokens: tokenize(s)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), ParseError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(ParseError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        match self.next()? {
+            (_, t) if t == expected => Ok(()),
+            (pos, t) => Err(ParseError::Unexpected(pos, t.to_string())),
+        }
+    }
+
+    /// 要求输入已经结束
+    fn end(&self) -> Result<(), ParseError> {
+        match self.tokens.get(self.pos) {
+            Some((pos, t)) => Err(ParseError::Unexpected(*pos, t.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Parser {
+    fn infix_implies(&mut self) -> Result<LtlNode, ParseError> {
+        let lhs = self.infix_or()?;
+        if self.peek() != Some(&Token::Arrow) {
+            return Ok(lhs);
+        }
+        self.pos += 1;
+        Ok(LtlNode::Implies(
+            Box::new(lhs),
+            Box::new(self.infix_implies()?),
+        ))
+    }
+
+    fn infix_or(&mut self) -> Result<LtlNode, ParseError> {
+        let mut lhs = self.infix_and()?;
+        while self.peek() == Some(&Token::Punct('|')) {
+            self.pos += 1;
+            lhs = LtlNode::Or(Box::new(lhs), Box::new(self.infix_and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn infix_and(&mut self) -> Result<LtlNode, ParseError> {
+        let mut lhs = self.infix_binary()?;
+        while self.peek() == Some(&Token::Punct('&')) {
+            self.pos += 1;
+            lhs = LtlNode::And(Box::new(lhs), Box::new(self.infix_binary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn infix_binary(&mut self) -> Result<LtlNode, ParseError> {
+        let lhs = self.infix_unary()?;
+        let op = match self.peek() {
+            Some(Token::Ident(op)) if BINARY.contains(&op.as_str()) => op.clone(),
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+        let (lhs, rhs) = (Box::new(lhs), Box::new(self.infix_binary()?));
+        Ok(match op.as_str() {
+            "U" => LtlNode::Until(lhs, rhs),
+            "R" => LtlNode::Release(lhs, rhs),
+            "S" => LtlNode::Since(lhs, rhs),
+            _ => LtlNode::Trigger(lhs, rhs),
+        })
+    }
+
+    fn infix_unary(&mut self) -> Result<LtlNode, ParseError> {
+        let (pos, token) = self.next()?;
+        Ok(match token {
+            Token::Punct('!') => negate(self.infix_unary()?),
+            Token::Punct('(') => {
+                let node = self.infix_implies()?;
+                self.expect(Token::Punct(')'))?;
+                node
+            }
+            Token::Ident(word) => match word.as_str() {
+                "X" => LtlNode::Next(Box::new(self.infix_unary()?)),
+                "N" => LtlNode::WNext(Box::new(self.infix_unary()?)),
+                "F" => LtlNode::Eventually(Box::new(self.infix_unary()?)),
+                "G" => LtlNode::Always(Box::new(self.infix_unary()?)),
+                "Y" => LtlNode::Yesterday(Box::new(self.infix_unary()?)),
+                "Z" => LtlNode::WYesterday(Box::new(self.infix_unary()?)),
+                "O" => LtlNode::Once(Box::new(self.infix_unary()?)),
+                "H" => LtlNode::Historically(Box::new(self.infix_unary()?)),
+                "true" => LtlNode::True,
+                "false" => LtlNode::False,
+                w if BINARY.contains(&w) => return Err(ParseError::Unexpected(pos, word)),
+                _ => LtlNode::Literal(true, word),
+            },
+            token => return Err(ParseError::Unexpected(pos, token.to_string())),
+        })
+    }
+}
+
+impl Parser {
+    fn tuple(&mut self) -> Result<LtlNode, ParseError> {
+        match self.next()? {
+            (_, Token::Str(word)) => return Ok(LtlNode::Literal(true, word)),
+            (_, Token::Ident(word)) if word == "True" => return Ok(LtlNode::True),
+            (_, Token::Ident(word)) if word == "False" => return Ok(LtlNode::False),
+            (_, Token::Punct('(')) => {}
+            (pos, token) => return Err(ParseError::Unexpected(pos, token.to_string())),
+        }
+        let (op_pos, op) = match self.next()? {
+            (pos, Token::Str(op)) => (pos, op),
+            (pos, token) => return Err(ParseError::Unexpected(pos, token.to_string())),
+        };
+        self.expect(Token::Punct(','))?;
+        let lhs = Box::new(self.tuple()?);
+        let node = match op.as_str() {
+            "!" => negate(*lhs),
+            "X" => LtlNode::Next(lhs),
+            "N" => LtlNode::WNext(lhs),
+            "F" => LtlNode::Eventually(lhs),
+            "G" => LtlNode::Always(lhs),
+            "Y" => LtlNode::Yesterday(lhs),
+            "Z" => LtlNode::WYesterday(lhs),
+            "O" => LtlNode::Once(lhs),
+            "H" => LtlNode::Historically(lhs),
+            "&" | "|" | "U" | "R" | "S" | "T" | "->" => {
+                self.expect(Token::Punct(','))?;
+                let rhs = Box::new(self.tuple()?);
+                match op.as_str() {
+                    "&" => LtlNode::And(lhs, rhs),
+                    "|" => LtlNode::Or(lhs, rhs),
+                    "U" => LtlNode::Until(lhs, rhs),
+                    "R" => LtlNode::Release(lhs, rhs),
+                    "S" => LtlNode::Since(lhs, rhs),
+                    "T" => LtlNode::Trigger(lhs, rhs),
+                    _ => LtlNode::Implies(lhs, rhs),
+                }
+            }
+            _ => return Err(ParseError::Unexpected(op_pos, format!("'{}'", op))),
+        };
+        // 允许末尾多余的逗号
+        if self.peek() == Some(&Token::Punct(',')) {
+            self.pos += 1;
+        }
+        self.expect(Token::Punct(')'))?;
+        Ok(node)
+    }
+}
+
+/// `!` 作用于字面量时得到相反的字面量，否则为 `LtlNode::Not`，从而与 `Display` 的输出一致
+fn negate(node: LtlNode) -> LtlNode {
+    match node {
+        LtlNode::Literal(pos, name) => LtlNode::Literal(!pos, name),
+        node => LtlNode::Not(Box::new(node)),
+    }
+}
+
+impl std::str::FromStr for LtlNode {
+    type Err = ParseError;
+    /// 解析中缀语法
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let node = parser.infix_implies()?;
+        parser.end()?;
+        Ok(node)
+    }
+}
+
+/// 解析元组语法
+pub fn parse_tuple(s: &str) -> Result<LtlNode, ParseError> {
+    let mut parser = Parser::new(s)?;
+    let node = parser.tuple()?;
+    parser.end()?;
+    Ok(node)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ltl::TupleLtlNode;
+
+    fn lit(pos: bool, name: &str) -> Box<LtlNode> {
+        Box::new(LtlNode::Literal(pos, name.to_string()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let ltl = LtlNode::Or(
+            Box::new(LtlNode::And(
+                lit(true, "p"),
+                Box::new(LtlNode::Until(
+                    lit(true, "q"),
+                    Box::new(LtlNode::Next(lit(false, "r"))),
+                )),
+            )),
+            Box::new(LtlNode::Release(
+                Box::new(LtlNode::Always(Box::new(LtlNode::True))),
+                Box::new(LtlNode::WNext(Box::new(LtlNode::Eventually(Box::new(
+                    LtlNode::False,
+                ))))),
+            )),
+        );
+        let ltl = LtlNode::Since(
+            Box::new(LtlNode::Trigger(
+                Box::new(LtlNode::Yesterday(lit(true, "p"))),
+                Box::new(LtlNode::WYesterday(lit(false, "q"))),
+            )),
+            Box::new(LtlNode::And(
+                Box::new(LtlNode::Once(lit(true, "r"))),
+                Box::new(LtlNode::Historically(Box::new(ltl))),
+            )),
+        );
+        assert_eq!(format!("{}", ltl).parse::<LtlNode>(), Ok(ltl.clone()));
+        assert_eq!(
+            TupleLtlNode::parse(&format!("{}", TupleLtlNode(&ltl))),
+            Ok(ltl)
+        );
+    }
+
+    #[test]
+    fn relaxed() {
+        assert_eq!(
+            "p U X !q".parse::<LtlNode>(),
+            "(p U (X (!(q))))".parse::<LtlNode>()
+        );
+        assert_eq!(
+            "a && b || F c_1 & true".parse::<LtlNode>(),
+            Ok(LtlNode::Or(
+                Box::new(LtlNode::And(lit(true, "a"), lit(true, "b"))),
+                Box::new(LtlNode::And(
+                    Box::new(LtlNode::Eventually(lit(true, "c_1"))),
+                    Box::new(LtlNode::True),
+                )),
+            ))
+        );
+        assert_eq!(
+            "a U b R c".parse::<LtlNode>(),
+            Ok(LtlNode::Until(
+                lit(true, "a"),
+                Box::new(LtlNode::Release(lit(true, "b"), lit(true, "c"))),
+            ))
+        );
+        assert_eq!(
+            "!(p U X q)".parse::<LtlNode>(),
+            Ok(LtlNode::Not(Box::new(LtlNode::Until(
+                lit(true, "p"),
+                Box::new(LtlNode::Next(lit(true, "q"))),
+            ))))
+        );
+        assert_eq!("!!p".parse::<LtlNode>(), Ok(*lit(true, "p")));
+        assert_eq!(
+            parse_tuple(r#"("U", 'p', ('X', ('!', 'q'),),)"#),
+            "p U X !q".parse::<LtlNode>()
+        );
+    }
+
+    #[test]
+    fn implies() {
+        let ltl = LtlNode::Always(Box::new(LtlNode::Implies(
+            lit(true, "req"),
+            Box::new(LtlNode::Eventually(lit(true, "grant"))),
+        )));
+        assert_eq!(format!("{}", ltl).parse::<LtlNode>(), Ok(ltl.clone()));
+        assert_eq!(
+            TupleLtlNode::parse(&format!("{}", TupleLtlNode(&ltl))),
+            Ok(ltl.clone())
+        );
+        assert_eq!("G (req -> F grant)".parse::<LtlNode>(), Ok(ltl));
+        assert_eq!(
+            "a -> b | c -> d".parse::<LtlNode>(),
+            Ok(LtlNode::Implies(
+                lit(true, "a"),
+                Box::new(LtlNode::Implies(
+                    Box::new(LtlNode::Or(lit(true, "b"), lit(true, "c"))),
+                    lit(true, "d"),
+                )),
+            ))
+        );
+        assert_eq!(
+            "!(a -> b)".parse::<LtlNode>(),
+            Ok(LtlNode::Not(Box::new(LtlNode::Implies(
+                lit(true, "a"),
+                lit(true, "b")
+            ))))
+        );
+        assert_eq!(
+            "a - b".parse::<LtlNode>(),
+            Err(ParseError::Unexpected(2, "-".to_string()))
+        );
+    }
+
+    /// 含有 `!` 与 `->` 的公式输出后能解析为原来的公式
+    #[test]
+    fn negation_round_trip() {
+        let formulas = [
+            LtlNode::Not(Box::new(LtlNode::Next(lit(true, "p")))),
+            LtlNode::Not(Box::new(LtlNode::Not(Box::new(LtlNode::Eventually(lit(
+                false, "p",
+            )))))),
+            LtlNode::Implies(
+                Box::new(LtlNode::Not(Box::new(LtlNode::Until(
+                    lit(true, "p"),
+                    lit(false, "q"),
+                )))),
+                Box::new(LtlNode::Not(Box::new(LtlNode::True))),
+            ),
+            LtlNode::Not(Box::new(LtlNode::Implies(lit(false, "p"), lit(true, "q")))),
+        ];
+        for ltl in formulas.iter() {
+            assert_eq!(format!("{}", ltl).parse::<LtlNode>().as_ref(), Ok(ltl));
+            assert_eq!(
+                TupleLtlNode::parse(&format!("{}", TupleLtlNode(ltl))).as_ref(),
+                Ok(ltl)
+            );
+        }
+    }
+
+    #[test]
+    fn error() {
+        assert_eq!("(p U q".parse::<LtlNode>(), Err(ParseError::UnexpectedEnd));
+        assert_eq!(
+            "p q".parse::<LtlNode>(),
+            Err(ParseError::Unexpected(2, "q".to_string()))
+        );
+        assert_eq!("p U".parse::<LtlNode>(), Err(ParseError::UnexpectedEnd));
+        assert_eq!(
+            "p $ q".parse::<LtlNode>(),
+            Err(ParseError::Unexpected(2, "$".to_string()))
+        );
+        assert_eq!(
+            parse_tuple("('W', 'p', 'q')"),
+            Err(ParseError::Unexpected(1, "'W'".to_string()))
+        );
+    }
+}
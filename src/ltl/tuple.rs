@@ -1,34 +1,62 @@
+use super::parser::{parse_tuple, ParseError};
 use super::LtlNode;
 
 pub struct TupleLtlNode<'a>(pub &'a LtlNode);
 
+impl<'a> TupleLtlNode<'a> {
+    /// 解析元组语法，见 `ltl::parser`
+    pub fn parse(s: &str) -> Result<LtlNode, ParseError> {
+        parse_tuple(s)
+    }
+}
+
 impl<'a> std::fmt::Display for TupleLtlNode<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.0 {
             LtlNode::And(lhs, rhs) => f.write_fmt(format_args!(
                 "('&', {}, {})",
-                TupleLtlNode(&*lhs),
-                TupleLtlNode(&*rhs),
+                TupleLtlNode(lhs),
+                TupleLtlNode(rhs),
             )),
             LtlNode::Or(lhs, rhs) => f.write_fmt(format_args!(
                 "('|', {}, {})",
-                TupleLtlNode(&*lhs),
-                TupleLtlNode(&*rhs),
+                TupleLtlNode(lhs),
+                TupleLtlNode(rhs),
             )),
-            LtlNode::Next(lhs) => f.write_fmt(format_args!("('X', {})", TupleLtlNode(&*lhs))),
-            LtlNode::WNext(lhs) => f.write_fmt(format_args!("('N', {})", TupleLtlNode(&*lhs))),
+            LtlNode::Next(lhs) => f.write_fmt(format_args!("('X', {})", TupleLtlNode(lhs))),
+            LtlNode::WNext(lhs) => f.write_fmt(format_args!("('N', {})", TupleLtlNode(lhs))),
             LtlNode::Until(lhs, rhs) => f.write_fmt(format_args!(
                 "('U', {}, {})",
-                TupleLtlNode(&*lhs),
-                TupleLtlNode(&*rhs),
+                TupleLtlNode(lhs),
+                TupleLtlNode(rhs),
             )),
             LtlNode::Release(lhs, rhs) => f.write_fmt(format_args!(
                 "('R', {}, {})",
-                TupleLtlNode(&*lhs),
-                TupleLtlNode(&*rhs),
+                TupleLtlNode(lhs),
+                TupleLtlNode(rhs),
+            )),
+            LtlNode::Eventually(lhs) => f.write_fmt(format_args!("('F', {})", TupleLtlNode(lhs))),
+            LtlNode::Always(lhs) => f.write_fmt(format_args!("('G', {})", TupleLtlNode(lhs))),
+            LtlNode::Yesterday(lhs) => f.write_fmt(format_args!("('Y', {})", TupleLtlNode(lhs))),
+            LtlNode::WYesterday(lhs) => f.write_fmt(format_args!("('Z', {})", TupleLtlNode(lhs))),
+            LtlNode::Since(lhs, rhs) => f.write_fmt(format_args!(
+                "('S', {}, {})",
+                TupleLtlNode(lhs),
+                TupleLtlNode(rhs),
+            )),
+            LtlNode::Trigger(lhs, rhs) => f.write_fmt(format_args!(
+                "('T', {}, {})",
+                TupleLtlNode(lhs),
+                TupleLtlNode(rhs),
+            )),
+            LtlNode::Once(lhs) => f.write_fmt(format_args!("('O', {})", TupleLtlNode(lhs))),
+            LtlNode::Historically(lhs) => f.write_fmt(format_args!("('H', {})", TupleLtlNode(lhs))),
+            LtlNode::Not(lhs) => f.write_fmt(format_args!("('!', {})", TupleLtlNode(lhs))),
+            LtlNode::Implies(lhs, rhs) => f.write_fmt(format_args!(
+                "('->', {}, {})",
+                TupleLtlNode(lhs),
+                TupleLtlNode(rhs),
             )),
-            LtlNode::Eventually(lhs) => f.write_fmt(format_args!("('F', {})", TupleLtlNode(&*lhs))),
-            LtlNode::Always(lhs) => f.write_fmt(format_args!("('G', {})", TupleLtlNode(&*lhs))),
             LtlNode::Literal(pos, name) => {
                 if *pos {
                     f.write_fmt(format_args!("'{}'", name))
@@ -36,6 +64,8 @@ impl<'a> std::fmt::Display for TupleLtlNode<'a> {
                     f.write_fmt(format_args!("('!', '{}')", name))
                 }
             }
+            LtlNode::True => f.write_str("True"),
+            LtlNode::False => f.write_str("False"),
         }
     }
 }
@@ -1,131 +1,147 @@
-mod bool_logic;
-mod context;
-mod enforcers;
-mod ltl;
-// mod maple;
 mod options;
-mod sat;
-mod utils;
 
-use bool_logic::Variable;
-use clap::derive::Clap;
-use context::Context;
-use enforcers::{ContextEnforcer, Enforcer};
-use options::Opts;
-use sat::convert_cnf;
+use limeltl::{
+    learn_with_stats, verify, Dump, Input, LearnError, LearnOptions, LtlNode, TupleLtlNode,
+};
+use options::{Command, Opts, VerifyOpts};
 use std::io::Write;
 
-fn solve_iter(ctx: &Context, opts: &Opts, output: &mut impl Write) -> Result<(), ()> {
-    // 构造规则
-    let rules = ContextEnforcer.rules(&ctx);
-
-    if opts.fmt_expr {
-        for rule in rules.iter() {
-            writeln!(output, "{:?}", rule).expect("写入规则失败");
-        }
+/// 进程退出码
+fn exit_code(e: &LearnError) -> i32 {
+    match e {
+        LearnError::Unsatisfiable(_) => 1,
+        LearnError::Input(_) => 2,
+        LearnError::Io(_) => 3,
+        LearnError::Encoding(_) => 4,
+        LearnError::Decode(_) => 5,
+        LearnError::Mismatch { .. } => 6,
+        LearnError::Solver(_) => 7,
+        LearnError::Timeout { .. } => 8,
     }
+}
 
-    // 生成 CNF
-    let mut solver = minisat::Solver::new();
-    let mut conv = sat::SATConverter::new(&mut solver);
-    for rule in rules.into_iter() {
-        let rule_cnf = convert_cnf(rule);
-        conv.add_clause(rule_cnf);
-    }
-    let lit_pos = conv.get_var(&Variable::Exactly(true)).clone();
-    let lit_neg = !conv.get_var(&Variable::Exactly(false)).clone();
-    let vars = conv.finish();
-
-    if opts.fmt_cnf {
-        // minisat::dimacs::write_dimacs(output, &formula).expect("写入规则失败");
-    }
+/// 读取输入，`-` 表示标准输入
+fn read_input(path: &str) -> Result<Input, LearnError> {
+    let reader: Box<dyn std::io::Read> = if path != "-" {
+        Box::new(std::fs::File::open(path)?)
+    } else {
+        Box::new(std::io::stdin())
+    };
+    Ok(Input::from_reader(reader)?)
+}
 
-    // 求解
-    let result = solver.solve_under_assumptions(vec![lit_pos, lit_neg]);
+fn run(opts: Opts) -> Result<(), LearnError> {
+    // 读取输入
+    let input = read_input(opts.input.as_str())?;
+    // 打开输出文件
+    let mut output: Box<dyn std::io::Write> = if opts.output != "-" {
+        Box::new(std::fs::File::create(opts.output.as_str())?)
+    } else {
+        Box::new(std::io::stdout())
+    };
 
-    if opts.fmt_res {
-        writeln!(
-            output,
-            "n = {}, SAT = {}",
-            ctx.max_skeletons(),
-            result.is_ok()
-        )
-        .expect("写入失败");
-        for word in ctx.words() {
-            writeln!(output, "word {} => {}", word.0, word.1).unwrap();
-        }
-        if let Ok(ref model) = result {
-            for v in vars
-                .iter()
-                .filter(|(_, l)| model.value(l.clone()) == true)
-                .filter_map(|(v, _)| match v {
-                    Variable::Run(_, _, _) => None,
-                    Variable::Phantom(_) => None,
-                    Variable::Exactly(_) => None,
-                    s => Some(s),
-                })
-            {
-                writeln!(output, "{:?} = true", v).expect("写入失败");
-            }
-        }
+    let learn_opts = LearnOptions {
+        max_size: opts.size,
+        incremental: opts.incremental,
+        tseitin: opts.tseitin,
+        lenient: opts.lenient,
+        operators: opts.operators.clone(),
+        symmetry_breaking: opts.symmetry_breaking,
+        solver: opts.solver.clone(),
+        jobs: opts.jobs,
+        encode_jobs: opts.encode_jobs,
+        timeout: opts.timeout,
+        bound_timeout: opts.bound_timeout,
+        past_time: opts.past_time,
+        noise_tolerant: opts.noise_tolerant,
+        max_errors: opts.max_errors,
+        dedup: opts.dedup,
+        share_suffixes: opts.share_suffixes,
+        encoding: opts.encoding,
+        max_solutions: if opts.all {
+            usize::MAX
+        } else {
+            opts.max_solutions
+        },
+        dump: if opts.fmt_expr {
+            Some(Dump::Expr)
+        } else if opts.fmt_cnf {
+            Some(Dump::Cnf)
+        } else if opts.fmt_res {
+            Some(Dump::Result)
+        } else {
+            None
+        },
+        verbose: true,
+    };
+    let mut stats = vec![];
+    let result = learn_with_stats(input, learn_opts, &mut output, &mut stats);
+    // 学习失败时也写出已经求解的尺寸限制的统计
+    if let Some(path) = opts.stats.as_ref() {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(file, &stats).map_err(std::io::Error::from)?;
     }
-
-    // 没有结果
-    if !result.is_ok() {
-        eprintln!("SAT 求解不可满足，n = {:?}", ctx.max_skeletons());
-        return Err(());
-    } else {
-        eprintln!("SAT 求解可满足，n = {:?}", ctx.max_skeletons());
+    let result = result?;
+    if opts.noise_tolerant || opts.max_errors.is_some() {
+        eprintln!(
+            "不被满足的正例：{:?}，反例：{:?}，代价之和为 {}",
+            result.sacrificed_pos, result.sacrificed_neg, result.sacrificed_cost
+        );
     }
 
-    // 生成语法树
-    let model = result.unwrap();
-    let pos_vars: Vec<Variable> = vars
-        .iter()
-        .filter(|(_, l)| model.value(l.clone()) == true)
-        .map(|(v, _)| v.clone())
-        .collect();
-    let model = ltl::Model::new(&ctx, &pos_vars);
-    let ltl = model.make_ltl(0);
-
-    if opts.fmt_ltl() || opts.fmt_both {
-        writeln!(output, "{}", ltl).expect("写入失败");
-    }
+    for formula in std::iter::once(&result.formula).chain(result.alternatives.iter()) {
+        if opts.fmt_ltl() || opts.fmt_both {
+            writeln!(output, "{}", formula)?;
+        }
 
-    if opts.fmt_tuple || opts.fmt_both {
-        writeln!(output, "{}", ltl::TupleLtlNode(&ltl)).expect("写入失败");
+        if opts.fmt_tuple || opts.fmt_both {
+            writeln!(output, "{}", TupleLtlNode(formula))?;
+        }
     }
 
     Ok(())
 }
 
-fn main() -> Result<(), &'static str> {
-    let opts: Opts = Opts::parse();
-
-    // 读取输入
-    let input =
-        serde_json::from_reader::<Box<dyn std::io::Read>, context::Input>(if opts.input != "-" {
-            Box::new(std::fs::File::open(opts.input.as_str()).expect("无法打开输入文件"))
-        } else {
-            Box::new(std::io::stdin())
-        })
-        .expect("无法解析 JSON");
-    // 打开输出文件
-    let mut output: Box<dyn std::io::Write> = if opts.output != "-" {
-        Box::new(std::fs::File::create(opts.output.as_str()).expect("无法打开输出文件"))
+/// 检验公式，返回公式是否接受所有正例且拒绝所有反例
+fn run_verify(opts: VerifyOpts) -> Result<bool, Box<dyn std::error::Error>> {
+    let input = read_input(opts.input.as_str())?;
+    input.validate()?;
+    let formula = if opts.tuple {
+        TupleLtlNode::parse(opts.formula.as_str())?
     } else {
-        Box::new(std::io::stdout())
+        opts.formula.parse::<LtlNode>()?
     };
 
-    // 解析输入
-    let mut ctx: context::Context = input.into();
+    let result = verify(&input, &formula);
+    println!("{}", formula);
+    println!(
+        "正例：接受 {:?}，拒绝 {:?}",
+        result.accepted_pos, result.rejected_pos
+    );
+    println!(
+        "反例：接受 {:?}，拒绝 {:?}",
+        result.accepted_neg, result.rejected_neg
+    );
+    Ok(result.is_consistent())
+}
 
-    for n in 2..=opts.size {
-        ctx.set_size_bound(n);
-        if solve_iter(&ctx, &opts, &mut output).is_ok() {
-            return Ok(());
+fn main() {
+    match options::parse() {
+        Command::Verify(opts) => std::process::exit(match run_verify(opts) {
+            Ok(consistent) => !consistent as i32,
+            Err(e) => {
+                eprintln!("错误：{}", e);
+                match e.downcast_ref::<LearnError>() {
+                    Some(LearnError::Io(_)) => 3,
+                    _ => 2,
+                }
+            }
+        }),
+        Command::Learn(opts) => {
+            if let Err(e) = run(opts) {
+                eprintln!("错误：{}", e);
+                std::process::exit(exit_code(&e));
+            }
         }
     }
-
-    Err("无法在给定限制内求解")
 }
@@ -2,11 +2,26 @@
 //
 // This example demonstrates clap's full 'custom derive' style of creating arguments which is the
 // simplest method of use, but sacrifices some flexibility.
-use clap::{crate_version, ArgGroup, Clap};
+use clap::{crate_version, AppSettings, ArgGroup, Clap, ErrorKind, FromArgMatches, IntoApp};
+use limeltl::{Backend, Encoding, Operator};
+use std::time::Duration;
+
+/// 解析以秒为单位的时限，可以带小数
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(format!("{:?} 不是合法的秒数", s)),
+    }
+}
 
 /// 根据输入序列学习 LTL 公式
 ///
 /// 基于 https://aaai.org/ojs/index.php/ICAPS/article/view/3529
+///
+/// 使用 `limeltl verify` 检验公式在输入例子上的结果。
+///
+/// 退出码：0 成功，1 在给定限制内不可满足，2 输入不合法，3 读写失败，4 编码错误，5 无法构造公式，
+/// 6 求得的公式与例子不符，7 求解器运行失败，8 超过时限
 #[derive(Clap, Debug)]
 #[clap(version = crate_version!(), author = "FU Yuze <i@xfox.me>")]
 #[clap(group = ArgGroup::new("format"))]
@@ -33,12 +48,142 @@ pub struct Opts {
     /// 同时生成 LTLf 和元组，每个一行
     #[clap(short = "b", long = "both", group = "format")]
     pub fmt_both: bool,
+    /// 在不同尺寸限制之间增量求解，复用求解器状态与学习到的子句
+    #[clap(short = "i", long = "incremental")]
+    pub incremental: bool,
+    /// 同时求解的尺寸限制数量，每个尺寸限制使用独立的求解器，结果与依次求解相同
+    #[clap(
+        short = "j",
+        long = "jobs",
+        default_value = "1",
+        conflicts_with = "incremental"
+    )]
+    pub jobs: usize,
+    /// 生成例子规则的线程数量，生成的规则与依次生成相同，但需要同时保存每批例子的规则；
+    /// 可以与 `--incremental` 同时使用
+    #[clap(long = "encode-jobs", default_value = "1")]
+    pub encode_jobs: usize,
+    /// 使用 Tseitin 变换生成 CNF，而不是按分配律展开
+    #[clap(long = "tseitin")]
+    pub tseitin: bool,
+    /// 将例子中出现的单词表以外的单词加入单词表，而不是报告错误
+    #[clap(long = "lenient")]
+    pub lenient: bool,
+    /// 只使用给定的运算符，以逗号分隔，如 `G,F,X,&,|`；默认使用所有将来时运算符，
+    /// `!`、`->`、`true`、`false` 需要显式指定
+    #[clap(long = "operators", use_delimiter = true)]
+    pub operators: Option<Vec<Operator>>,
+    /// 要求节点按照广度优先的顺序编号，减少求解器需要排除的同构解
+    #[clap(long = "symmetry-breaking")]
+    pub symmetry_breaking: bool,
+    /// 使用的 SAT 求解器：`minisat`、`varisat` 或 `external:<命令>`，外部求解器以
+    /// DIMACS CNF 文件的路径作为最后一个参数调用，需要按照 SAT 竞赛格式输出结果
+    #[clap(long = "solver", default_value = "minisat")]
+    pub solver: Backend,
+    /// 整个学习过程的时限（秒），超过时报告已证明不可满足的最大尺寸限制；不能与 `--solver varisat`
+    /// 同时使用
+    #[clap(long = "timeout", parse(try_from_str = parse_seconds))]
+    pub timeout: Option<Duration>,
+    /// 每个尺寸限制的时限（秒），超过时报告已证明不可满足的最大尺寸限制；不能与 `--solver varisat`
+    /// 同时使用
+    #[clap(long = "bound-timeout", parse(try_from_str = parse_seconds))]
+    pub bound_timeout: Option<Duration>,
+    /// 额外使用过去时运算符 `Y`、`Z`（弱 `Y`）、`S`、`T`、`O`、`H`
+    #[clap(long = "past")]
+    pub past_time: bool,
+    /// 允许例子不被满足，并使不被满足的例子的代价（权重）之和最小
+    #[clap(long = "noise-tolerant")]
+    pub noise_tolerant: bool,
+    /// 允许不被满足的例子的代价之和至多为指定值
+    #[clap(long = "max-errors")]
+    pub max_errors: Option<usize>,
+    /// 合并序列与循环起点都相同的同类例子，允许例子不被满足时代价相加
+    #[clap(long = "dedup")]
+    pub dedup: bool,
+    /// 在后缀相同的同类有穷例子之间共享 Run 变量，减少变量与规则数量；使用过去时运算符或后缀树编码时无效
+    #[clap(long = "share-suffixes")]
+    pub share_suffixes: bool,
+    /// 例子的编码方式：`example` 逐个例子编码，`trie` 以所有有穷例子的后缀树编码，正例与反例共用
+    /// 相同后缀的变量；套索序列依然逐个例子编码，`trie` 不能与过去时运算符同时使用
+    #[clap(long = "encoding", default_value = "example")]
+    pub encoding: Encoding,
+    /// 求得所有满足最小尺寸限制的公式
+    #[clap(long = "all", conflicts_with = "max-solutions")]
+    pub all: bool,
+    /// 最多求得的满足最小尺寸限制的公式数量
+    #[clap(long = "max-solutions", default_value = "1")]
+    pub max_solutions: usize,
+    /// 将每个尺寸限制的统计（规则数量、变量与子句数量、编码与求解用时、冲突与决策次数）
+    /// 以 JSON 写入指定文件
+    #[clap(long = "stats")]
+    pub stats: Option<String>,
     /// 指定生成 AFA 大小
     #[clap(short = "n", long = "size", required = true)]
     pub size: usize,
 }
 
+/// 检验公式在输入例子上的结果
+///
+/// 退出码：0 公式接受所有正例且拒绝所有反例，1 存在不符合的例子，2 输入不合法，3 读写失败
+#[derive(Clap, Debug)]
+#[clap(name = "verify", version = crate_version!(), author = "FU Yuze <i@xfox.me>")]
+pub struct VerifyOpts {
+    /// 观察到的现象输入 JSON 文件名
+    pub input: String,
+    /// 待检验的 LTLf 公式
+    pub formula: String,
+    /// 以 Python 元组语法解析公式
+    #[clap(short = "t", long = "tuple")]
+    pub tuple: bool,
+}
+
+/// 命令行指定的操作
+#[derive(Debug)]
+pub enum Command {
+    /// 学习公式
+    Learn(Opts),
+    /// 检验公式
+    Verify(VerifyOpts),
+}
+
+/// 解析命令行参数，遇到错误时退出
+///
+/// `verify` 子命令与学习所需的参数互不相关，因此使用子命令时不要求给出学习所需的参数；
+/// `Opts` 中的参数都不是可选的，只能在解析后再根据子命令选择构造 `Opts` 或 `VerifyOpts`。
+pub fn parse() -> Command {
+    let matches = Opts::into_app()
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(VerifyOpts::into_app())
+        .get_matches();
+    match matches.subcommand_matches("verify") {
+        Some(matches) => Command::Verify(VerifyOpts::from_arg_matches(matches)),
+        None => {
+            let opts = Opts::from_arg_matches(&matches);
+            if let Some(conflict) = opts.conflict() {
+                clap::Error::with_description(
+                    format!("{}\n", conflict),
+                    ErrorKind::ArgumentConflict,
+                )
+                .expect("无法写出错误信息")
+                .exit();
+            }
+            Command::Learn(opts)
+        }
+    }
+}
+
 impl Opts {
+    /// clap 无法表达的参数冲突
+    fn conflict(&self) -> Option<&'static str> {
+        // Varisat 无法中断，求解开始后不会停止
+        if self.solver == Backend::Varisat
+            && (self.timeout.is_some() || self.bound_timeout.is_some())
+        {
+            return Some("--solver varisat 不支持 --timeout 与 --bound-timeout");
+        }
+        None
+    }
+
     pub fn fmt_ltl(&self) -> bool {
         !self.fmt_expr && !self.fmt_cnf && !self.fmt_res && !self.fmt_tuple && !self.fmt_both
     }
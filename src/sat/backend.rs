@@ -0,0 +1,307 @@
+//! SAT 求解器后端
+//!
+//! `SATConverter` 只通过 `SatBackend` 使用求解器，变量以从 `0` 开始的编号表示，
+//! 字面量为 `(变量编号, 是否为正)`。
+
+use super::external::ExternalBackend;
+use super::minisat_backend::MinisatBackend;
+use super::varisat_backend::VarisatBackend;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// SAT 求解器的最小接口
+pub trait SatBackend: std::fmt::Debug {
+    /// 创建新变量，返回其编号，编号从 `0` 开始依次分配
+    fn new_var(&mut self) -> usize;
+
+    /// 添加析取子句
+    fn add_clause(&mut self, lits: &[(usize, bool)]);
+
+    /// 在给定假设下求解，返回是否可满足；达到 `limit` 时尽快停止并返回
+    /// `SolverError::Timeout` 或 `SolverError::Cancelled`，之后仍可继续使用
+    fn solve(&mut self, assumptions: &[(usize, bool)], limit: &Limit) -> Result<bool, SolverError>;
+
+    /// 上一次可满足的求解中变量的取值
+    fn value(&self, var: usize) -> bool;
+
+    /// 求解器创建以来的累计统计，不支持时返回 `None`
+    fn stats(&self) -> Option<SolverStats> {
+        None
+    }
+}
+
+/// 求解器的累计统计
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct SolverStats {
+    /// 冲突次数
+    pub conflicts: u64,
+    /// 决策次数
+    pub decisions: u64,
+}
+
+/// 提前停止求解的条件
+#[derive(Debug, Clone, Default)]
+pub struct Limit {
+    /// 截止时间
+    pub deadline: Option<Instant>,
+    /// 由其它线程设置，设置后尽快停止求解
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Limit {
+    /// 检查取消标志的间隔
+    const POLL: Duration = Duration::from_millis(10);
+
+    /// 是否没有任何停止条件
+    pub fn is_unlimited(&self) -> bool {
+        self.deadline.is_none() && self.cancel.is_none()
+    }
+
+    /// 若已达到停止条件则返回对应的错误，取消优先于超时
+    pub fn check(&self) -> Result<(), SolverError> {
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|c| c.load(Ordering::Relaxed))
+        {
+            Err(SolverError::Cancelled)
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Err(SolverError::Timeout)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 距离下一次需要调用 `check` 的时间
+    pub fn next_check(&self) -> Duration {
+        let deadline = self
+            .deadline
+            .map(|d| d.saturating_duration_since(Instant::now()));
+        match (deadline, self.cancel.is_some()) {
+            (Some(d), true) => d.min(Self::POLL),
+            (Some(d), false) => d,
+            (None, _) => Self::POLL,
+        }
+    }
+}
+
+/// 求解器运行失败
+#[derive(Debug)]
+pub enum SolverError {
+    /// 无法写出问题或启动外部求解器
+    Io(std::io::Error),
+    /// 外部求解器的输出无法解析
+    Output(String),
+    /// 求解器内部错误
+    Internal(String),
+    /// 超过时限，没有得到结果
+    Timeout,
+    /// 被其它线程取消，没有得到结果
+    Cancelled,
+}
+
+impl std::fmt::Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverError::Io(e) => write!(f, "无法运行外部求解器：{}", e),
+            SolverError::Output(s) => write!(f, "无法解析外部求解器的输出：{}", s),
+            SolverError::Internal(s) => write!(f, "求解器内部错误：{}", s),
+            SolverError::Timeout => f.write_str("求解超时"),
+            SolverError::Cancelled => f.write_str("求解被取消"),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SolverError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SolverError {
+    fn from(e: std::io::Error) -> Self {
+        SolverError::Io(e)
+    }
+}
+
+/// 可选的求解器
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub enum Backend {
+    /// MiniSat（默认）
+    #[default]
+    Minisat,
+    /// Varisat，纯 Rust 实现
+    Varisat,
+    /// 外部求解器命令，问题以 DIMACS CNF 文件的路径作为最后一个参数传入，
+    /// 求解器需要按照 SAT 竞赛格式输出 `s` 与 `v` 行
+    External(String),
+}
+
+impl Backend {
+    /// 创建求解器
+    pub fn create(&self) -> Box<dyn SatBackend> {
+        match self {
+            Backend::Minisat => Box::new(MinisatBackend::new()),
+            Backend::Varisat => Box::new(VarisatBackend::new()),
+            Backend::External(command) => Box::new(ExternalBackend::new(command)),
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Minisat => f.write_str("minisat"),
+            Backend::Varisat => f.write_str("varisat"),
+            Backend::External(command) => write!(f, "external:{}", command),
+        }
+    }
+}
+
+/// 未知的求解器
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UnknownBackend(pub String);
+
+impl std::fmt::Display for UnknownBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "未知的求解器 {:?}，可选 minisat、varisat 或 external:<命令>",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownBackend {}
+
+impl std::str::FromStr for Backend {
+    type Err = UnknownBackend;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minisat" => Ok(Backend::Minisat),
+            "varisat" => Ok(Backend::Varisat),
+            s => match s.strip_prefix("external:") {
+                Some(command) if !command.trim().is_empty() => {
+                    Ok(Backend::External(command.trim().to_string()))
+                }
+                _ => Err(UnknownBackend(s.to_string())),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `(a | b) & (!a | b) & (a | !b)` 只有 `a = b = true` 一个解
+    fn simple(backend: &mut dyn SatBackend) {
+        let a = backend.new_var();
+        let b = backend.new_var();
+        assert_eq!((a, b), (0, 1));
+        backend.add_clause(&[(a, true), (b, true)]);
+        backend.add_clause(&[(a, false), (b, true)]);
+        backend.add_clause(&[(a, true), (b, false)]);
+        assert!(backend.solve(&[], &Limit::default()).unwrap());
+        assert!(backend.value(a) && backend.value(b));
+        assert!(!backend.solve(&[(a, false)], &Limit::default()).unwrap());
+        assert!(backend.solve(&[(b, true)], &Limit::default()).unwrap());
+        backend.add_clause(&[(a, false), (b, false)]);
+        assert!(!backend.solve(&[], &Limit::default()).unwrap());
+    }
+
+    #[test]
+    fn in_process() {
+        simple(Backend::Minisat.create().as_mut());
+        simple(Backend::Varisat.create().as_mut());
+    }
+
+    /// 鸽巢问题：`n + 1` 只鸽子放入 `n` 个巢中，不可满足且对 CDCL 求解器很困难
+    fn pigeonhole(backend: &mut dyn SatBackend, n: usize) {
+        let x: Vec<Vec<usize>> = (0..=n)
+            .map(|_| (0..n).map(|_| backend.new_var()).collect())
+            .collect();
+        for row in x.iter() {
+            let clause: Vec<(usize, bool)> = row.iter().map(|v| (*v, true)).collect();
+            backend.add_clause(&clause);
+        }
+        for (p, row) in x.iter().enumerate() {
+            for other in x[..p].iter() {
+                for (a, b) in row.iter().zip(other.iter()) {
+                    backend.add_clause(&[(*a, false), (*b, false)]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        // Varisat 只在开始求解前检查时限
+        for (backend, interruptible) in [(Backend::Minisat, true), (Backend::Varisat, false)].iter()
+        {
+            let mut backend = backend.create();
+            pigeonhole(backend.as_mut(), 11);
+            let c = backend.new_var();
+            backend.add_clause(&[(c, true)]);
+            let limit = Limit {
+                deadline: Some(Instant::now() + Duration::from_millis(100)),
+                cancel: None,
+            };
+            if *interruptible {
+                assert!(matches!(
+                    backend.solve(&[], &limit),
+                    Err(SolverError::Timeout)
+                ));
+                let stats = backend.stats().unwrap();
+                assert!(stats.conflicts > 0 && stats.decisions >= stats.conflicts);
+                let cancel = Arc::new(AtomicBool::new(false));
+                let limit = Limit {
+                    deadline: None,
+                    cancel: Some(cancel.clone()),
+                };
+                let canceller = std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(100));
+                    cancel.store(true, Ordering::Relaxed);
+                });
+                assert!(matches!(
+                    backend.solve(&[], &limit),
+                    Err(SolverError::Cancelled)
+                ));
+                canceller.join().unwrap();
+            }
+            // 已经超过时限
+            let limit = Limit {
+                deadline: Some(Instant::now()),
+                cancel: None,
+            };
+            assert!(matches!(
+                backend.solve(&[], &limit),
+                Err(SolverError::Timeout)
+            ));
+            // 超时之后仍可继续求解
+            assert!(!backend.solve(&[(c, false)], &Limit::default()).unwrap());
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("minisat".parse(), Ok(Backend::Minisat));
+        assert_eq!(
+            "external:kissat -q".parse(),
+            Ok(Backend::External("kissat -q".to_string()))
+        );
+        assert_eq!(
+            "external:".parse::<Backend>(),
+            Err(UnknownBackend("external:".to_string()))
+        );
+        assert_eq!(
+            "glucose".parse::<Backend>(),
+            Err(UnknownBackend("glucose".to_string()))
+        );
+    }
+}
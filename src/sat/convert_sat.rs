@@ -1,67 +1,229 @@
 //! 将 CNF 形式的逻辑表达式转换为 SAT 求解器所需的格式
 
+use super::transformer::offset_phantoms;
+use super::{Dimacs, Limit, MinisatBackend, SatBackend, SolverError, SolverStats};
 use crate::bool_logic::{BinaryOp, PropExpr, UnaryOp, Variable};
-use minisat::{Bool, Solver};
-use std::{collections::HashMap, ops::Not};
+use std::collections::HashMap;
+use std::sync::{atomic::AtomicBool, Arc};
+use std::time::Instant;
 
+/// 添加的表达式不是 CNF 形式，意味着规则生成或 CNF 转换存在错误
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum EncodingError {
+    /// 表达式不是合取式
+    NotConjunction(String),
+    /// 合取式中的子句不是析取式
+    NotDisjunction(String),
+    /// 子句中存在嵌套的表达式
+    NestedExpr(String),
+    /// 析取式中没有任何文字，整个合取式不可满足
+    EmptyClause,
+}
+
+impl std::fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodingError::NotConjunction(e) => write!(f, "输入不是合取式：{}", e),
+            EncodingError::NotDisjunction(e) => write!(f, "输入不是析取式的合取：{}", e),
+            EncodingError::NestedExpr(e) => write!(f, "子句中存在嵌套的表达式：{}", e),
+            EncodingError::EmptyClause => write!(f, "子句为空"),
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+/// 持有 SAT 求解器，可以在多次求解之间保留已添加的子句与学习到的子句
 #[derive(Debug)]
-pub struct SATConverter<'a> {
-    pub vars: HashMap<Variable, Bool>, // TODO: pub for debug use
+pub struct SATConverter {
+    pub vars: HashMap<Variable, usize>, // TODO: pub for debug use
     // pub formula: CnfFormula,          // TODO: pub for debug use
-    solver: &'a mut Solver,
+    solver: Box<dyn SatBackend>,
+    clauses: usize,
+    /// 已分配的无关变量数量，见 `add_local_clause`
+    phantoms: usize,
+    dimacs: Option<Dimacs>,
+    limit: Limit,
 }
 
-impl<'a> SATConverter<'a> {
-    pub fn new(solver: &'a mut Solver) -> Self {
+impl SATConverter {
+    /// 使用 MiniSat 求解
+    pub fn new() -> Self {
+        Self::with_backend(Box::new(MinisatBackend::new()))
+    }
+
+    /// 使用给定的求解器求解，见 `sat::Backend`
+    pub fn with_backend(solver: Box<dyn SatBackend>) -> Self {
         Self {
             vars: HashMap::new(),
             solver,
+            clauses: 0,
+            phantoms: 0,
+            dimacs: None,
+            limit: Limit::default(),
         }
     }
+
+    /// 之后的求解超过 `deadline` 时停止并返回 `SolverError::Timeout`
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.limit.deadline = deadline;
+    }
+
+    /// 之后的求解在 `cancel` 被设置时停止并返回 `SolverError::Cancelled`
+    pub fn set_cancel(&mut self, cancel: Arc<AtomicBool>) {
+        self.limit.cancel = Some(cancel);
+    }
+
+    /// 同时记录添加的子句，以便导出 DIMACS CNF
+    pub fn with_dimacs(mut self) -> Self {
+        self.dimacs = Some(Dimacs::new());
+        self
+    }
 }
 
-impl<'a> SATConverter<'a> {
-    /// 获取变量
-    pub fn get_var(&mut self, v: &Variable) -> &Bool {
+impl Default for SATConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SATConverter {
+    /// 获取变量在求解器中的编号
+    pub fn get_var(&mut self, v: &Variable) -> usize {
         if !self.vars.contains_key(v) {
-            let r = self.solver.new_lit();
-            self.vars.insert(v.clone(), r);
+            let r = self.solver.new_var();
+            self.vars.insert(*v, r);
+            if let Some(dimacs) = self.dimacs.as_mut() {
+                dimacs.index(v);
+            }
         }
-        self.vars.get(v).unwrap()
+        self.vars[v]
     }
 
     /// 添加合取字句，要求字句必须是析取字句的合取，可以通过 `sat::covert_cnf` 获得。
-    pub fn add_clause(&mut self, expr: PropExpr) {
-        if let PropExpr::ChainedBinary(BinaryOp::Conjunction, clauses) = expr {
-            // 解析到子句
-            for clause in clauses.into_iter() {
-                if let PropExpr::ChainedBinary(BinaryOp::Disjunction, vars) = clause.clone() {
-                    // 构造变量
-                    let lits: Vec<Bool> = vars
-                        .into_iter()
-                        .map(|v| match v {
-                            PropExpr::Unary(UnaryOp::Negation, e) => match *e {
-                                PropExpr::Variable(v) => self.get_var(&v).clone().not(),
-                                _ => panic!("input has nested expr inside negation"),
-                            },
-                            PropExpr::Variable(v) => self.get_var(&v).clone(),
-                            _ => panic!("input has nested structure"),
-                        })
-                        .collect();
-                    if lits.len() > 0 {
-                        self.solver.add_clause(lits.into_iter());
-                    }
-                } else {
-                    panic!("input is not a conjunction of disjunction")
-                }
+    pub fn add_clause(&mut self, expr: PropExpr) -> Result<(), EncodingError> {
+        let clauses = match expr {
+            PropExpr::ChainedBinary(BinaryOp::Conjunction, clauses) => clauses,
+            expr => return Err(EncodingError::NotConjunction(format!("{:?}", expr))),
+        };
+        // 解析到子句
+        for clause in clauses.into_iter() {
+            let vars = match clause {
+                PropExpr::ChainedBinary(BinaryOp::Disjunction, vars) => vars,
+                clause => return Err(EncodingError::NotDisjunction(format!("{:?}", clause))),
+            };
+            // 构造变量
+            let lits = vars
+                .into_iter()
+                .map(|v| match v {
+                    PropExpr::Unary(UnaryOp::Negation, e) => match *e {
+                        PropExpr::Variable(v) => Ok((v, false)),
+                        e => Err(EncodingError::NestedExpr(format!("{:?}", !e))),
+                    },
+                    PropExpr::Variable(v) => Ok((v, true)),
+                    e => Err(EncodingError::NestedExpr(format!("{:?}", e))),
+                })
+                .collect::<Result<Vec<(Variable, bool)>, EncodingError>>()?;
+            if lits.is_empty() {
+                return Err(EncodingError::EmptyClause);
             }
+            let clause: Vec<(usize, bool)> =
+                lits.iter().map(|(v, p)| (self.get_var(v), *p)).collect();
+            self.solver.add_clause(&clause);
+            self.clauses += 1;
+            if let Some(dimacs) = self.dimacs.as_mut() {
+                dimacs.add_clause(&lits);
+            }
+        }
+        Ok(())
+    }
+
+    /// 添加 `sat::convert_cnf_local` 或 `sat::convert_tseitin_local` 的结果，其中 `phantoms` 个
+    /// 在表达式内编号的无关变量按照添加的顺序重新编号，不与之前添加的表达式中的无关变量冲突
+    pub fn add_local_clause(
+        &mut self,
+        expr: PropExpr,
+        phantoms: usize,
+    ) -> Result<(), EncodingError> {
+        let expr = if phantoms > 0 {
+            offset_phantoms(expr, self.phantoms)
         } else {
-            panic!("input is not a conjunction")
+            expr
+        };
+        self.phantoms += phantoms;
+        self.add_clause(expr)
+    }
+
+    /// 已创建的变量数量
+    pub fn var_count(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// 已添加的子句数量
+    pub fn clause_count(&self) -> usize {
+        self.clauses
+    }
+
+    /// 求解器的累计统计，见 `SatBackend::stats`
+    pub fn solver_stats(&self) -> Option<SolverStats> {
+        self.solver.stats()
+    }
+
+    /// 写出已记录的 DIMACS CNF，`assumptions` 作为单位子句附加在末尾；
+    /// 若未通过 `with_dimacs` 构造则不写出任何内容
+    pub fn write_dimacs(
+        &mut self,
+        output: &mut impl std::io::Write,
+        assumptions: &[(Variable, bool)],
+    ) -> std::io::Result<()> {
+        for (v, _) in assumptions.iter() {
+            self.get_var(v);
+        }
+        match self.dimacs.as_ref() {
+            Some(dimacs) => dimacs.write(output, assumptions),
+            None => Ok(()),
         }
     }
 
-    /// 结束构建，返回 `CnfFormula`
-    pub fn finish(self) -> HashMap<Variable, Bool> {
-        self.vars
+    /// 在给定假设 `(变量, 取值)` 下求解，若可满足则返回所有取值为真的变量
+    pub fn solve(
+        &mut self,
+        assumptions: &[(Variable, bool)],
+    ) -> Result<Option<Vec<Variable>>, SolverError> {
+        let lits: Vec<(usize, bool)> = assumptions
+            .iter()
+            .map(|(v, p)| (self.get_var(v), *p))
+            .collect();
+        if !self.solver.solve(&lits, &self.limit)? {
+            return Ok(None);
+        }
+        let solver = &self.solver;
+        Ok(Some(
+            self.vars
+                .iter()
+                .filter(|(_, i)| solver.value(**i))
+                .map(|(v, _)| *v)
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bool_logic::Variable::And as V;
+
+    #[test]
+    fn empty_clause() {
+        // 空的析取式不能被忽略，否则不可满足的约束会被当作可满足
+        let mut conv = SATConverter::new();
+        let expr = PropExpr::ChainedBinary(
+            BinaryOp::Conjunction,
+            vec![
+                PropExpr::ChainedBinary(BinaryOp::Disjunction, vec![V(1).into()]),
+                PropExpr::ChainedBinary(BinaryOp::Disjunction, vec![]),
+            ],
+        );
+        assert_eq!(conv.add_clause(expr), Err(EncodingError::EmptyClause));
     }
 }
@@ -0,0 +1,111 @@
+//! 以 DIMACS CNF 格式导出 SAT 问题，以便交给外部求解器或存档
+
+use crate::bool_logic::Variable;
+use std::{collections::HashMap, io::Write};
+
+/// 记录 SAT 问题的变量编号与子句
+///
+/// 变量编号从 `1` 开始，按照变量被创建的顺序分配。
+#[derive(Debug, Default)]
+pub struct Dimacs {
+    vars: Vec<Variable>,
+    indices: HashMap<Variable, usize>,
+    clauses: Vec<Vec<isize>>,
+}
+
+impl Dimacs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Dimacs {
+    /// 获取变量在 DIMACS 中的编号，若变量不存在则创建
+    pub fn index(&mut self, v: &Variable) -> usize {
+        if let Some(i) = self.indices.get(v) {
+            return *i;
+        }
+        self.vars.push(*v);
+        self.indices.insert(*v, self.vars.len());
+        self.vars.len()
+    }
+
+    /// 添加析取子句，每个字面量为 `(变量, 是否为正)`
+    pub fn add_clause(&mut self, lits: &[(Variable, bool)]) {
+        let clause = lits
+            .iter()
+            .map(|(v, p)| {
+                let i = self.index(v) as isize;
+                if *p {
+                    i
+                } else {
+                    -i
+                }
+            })
+            .collect();
+        self.clauses.push(clause);
+    }
+
+    /// 变量数量
+    pub fn var_count(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// 子句数量
+    pub fn clause_count(&self) -> usize {
+        self.clauses.len()
+    }
+
+    /// 写出 DIMACS CNF
+    ///
+    /// 文件头部的 `c` 注释给出每个编号对应的变量；`assumptions` 作为单位子句附加在末尾，
+    /// 使得导出的问题与带假设求解的问题等价。
+    pub fn write(
+        &self,
+        output: &mut impl Write,
+        assumptions: &[(Variable, bool)],
+    ) -> std::io::Result<()> {
+        for (i, v) in self.vars.iter().enumerate() {
+            writeln!(output, "c {} {:?}", i + 1, v)?;
+        }
+        writeln!(
+            output,
+            "p cnf {} {}",
+            self.var_count(),
+            self.clause_count() + assumptions.len()
+        )?;
+        for clause in self.clauses.iter() {
+            for lit in clause.iter() {
+                write!(output, "{} ", lit)?;
+            }
+            writeln!(output, "0")?;
+        }
+        for (v, p) in assumptions.iter() {
+            let i = self.indices[v] as isize;
+            writeln!(output, "{} 0", if *p { i } else { -i })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write() {
+        let mut dimacs = Dimacs::new();
+        dimacs.add_clause(&[(Variable::Run(0, 2, 3), true), (Variable::And(1), false)]);
+        dimacs.add_clause(&[(Variable::And(1), true)]);
+        dimacs.index(&Variable::Exactly(true));
+
+        let mut output = vec![];
+        dimacs
+            .write(&mut output, &[(Variable::Exactly(true), true)])
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "c 1 RUN(0, 2, 3)\nc 2 AND(1)\nc 3 true\np cnf 3 3\n1 -2 0\n2 0\n3 0\n"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use super::{Limit, SatBackend, SolverError};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// 用于生成不重复的临时文件名
+static PROBLEM_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// 每次求解时将问题写入临时的 DIMACS CNF 文件，并调用外部求解器
+///
+/// 命令按空白分隔为程序与参数，文件路径作为最后一个参数。求解器需要输出
+/// `s SATISFIABLE`（`s UNSATISFIABLE`）以及可满足时的 `v` 行，退出码不作检查。
+/// 达到停止条件时结束求解器进程。
+#[derive(Debug)]
+pub struct ExternalBackend {
+    command: String,
+    vars: usize,
+    clauses: Vec<Vec<(usize, bool)>>,
+    /// 上一次可满足的求解中变量的取值
+    model: Vec<bool>,
+}
+
+impl ExternalBackend {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            vars: 0,
+            clauses: vec![],
+            model: vec![],
+        }
+    }
+
+    /// 写出问题，`assumptions` 作为单位子句附加在末尾
+    fn write(&self, output: &mut impl Write, assumptions: &[(usize, bool)]) -> std::io::Result<()> {
+        writeln!(
+            output,
+            "p cnf {} {}",
+            self.vars,
+            self.clauses.len() + assumptions.len()
+        )?;
+        let units = assumptions.iter().map(std::slice::from_ref);
+        for clause in self.clauses.iter().map(Vec::as_slice).chain(units) {
+            for (var, positive) in clause.iter() {
+                let i = *var as isize + 1;
+                write!(output, "{} ", if *positive { i } else { -i })?;
+            }
+            writeln!(output, "0")?;
+        }
+        Ok(())
+    }
+
+    /// 运行求解器并返回其标准输出，达到停止条件时结束进程
+    fn run(&self, path: &Path, limit: &Limit) -> Result<String, SolverError> {
+        let mut args = self.command.split_whitespace();
+        let program = args.next().unwrap_or_default();
+        let mut child = Command::new(program)
+            .args(args)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // 在另一个线程中读取输出，避免管道写满时求解器阻塞
+        let mut stdout = child.stdout.take().expect("标准输出已重定向");
+        let reader = std::thread::spawn(move || {
+            let mut output = vec![];
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+        while child.try_wait()?.is_none() {
+            if let Err(e) = limit.check() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let output = reader.join().expect("读取线程不会崩溃")?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+}
+
+/// 解析 SAT 竞赛格式的输出，可满足时返回 `vars` 个变量的取值；有变量没有取值时视为输出错误
+fn parse_output(output: &str, vars: usize) -> Result<Option<Vec<bool>>, SolverError> {
+    let mut sat = None;
+    let mut model = vec![None; vars];
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("s") => match words.next() {
+                Some("SATISFIABLE") => sat = Some(true),
+                Some("UNSATISFIABLE") => sat = Some(false),
+                _ => return Err(SolverError::Output(line.to_string())),
+            },
+            Some("v") => {
+                for word in words {
+                    let lit: isize = word
+                        .parse()
+                        .map_err(|_| SolverError::Output(line.to_string()))?;
+                    let var = lit.unsigned_abs();
+                    if var > 0 && var <= vars {
+                        model[var - 1] = Some(lit > 0);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    match sat {
+        Some(true) => match model.iter().position(Option::is_none) {
+            Some(i) => Err(SolverError::Output(format!("变量 {} 没有取值", i + 1))),
+            None => Ok(Some(model.into_iter().flatten().collect())),
+        },
+        Some(false) => Ok(None),
+        None => Err(SolverError::Output("缺少 s 行".to_string())),
+    }
+}
+
+impl SatBackend for ExternalBackend {
+    fn new_var(&mut self) -> usize {
+        self.vars += 1;
+        self.vars - 1
+    }
+
+    fn add_clause(&mut self, lits: &[(usize, bool)]) {
+        self.clauses.push(lits.to_vec());
+    }
+
+    fn solve(&mut self, assumptions: &[(usize, bool)], limit: &Limit) -> Result<bool, SolverError> {
+        let path: PathBuf = std::env::temp_dir().join(format!(
+            "limeltl-{}-{}.cnf",
+            std::process::id(),
+            PROBLEM_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = (|| {
+            let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+            self.write(&mut file, assumptions)?;
+            file.flush()?;
+            drop(file);
+            parse_output(&self.run(&path, limit)?, self.vars)
+        })();
+        let _ = std::fs::remove_file(&path);
+        match result? {
+            Some(model) => {
+                self.model = model;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn value(&self, var: usize) -> bool {
+        self.model.get(var).copied().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn output() {
+        let out = "c comment\ns SATISFIABLE\nv 1 -2\nv 3 -4 0\n";
+        assert_eq!(
+            parse_output(out, 4).unwrap(),
+            Some(vec![true, false, true, false])
+        );
+        assert_eq!(parse_output("s UNSATISFIABLE\n", 2).unwrap(), None);
+        assert!(matches!(
+            parse_output("s UNKNOWN\n", 2),
+            Err(SolverError::Output(_))
+        ));
+        assert!(matches!(parse_output("", 2), Err(SolverError::Output(_))));
+        // 求解器没有给出所有变量的取值
+        assert!(matches!(
+            parse_output("s SATISFIABLE\nv 1 0\n", 2),
+            Err(SolverError::Output(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command() {
+        // 以 shell 脚本模拟外部求解器：检查问题的文件头并给出固定的解
+        let script = std::env::temp_dir().join(format!("limeltl-test-{}.sh", std::process::id()));
+        std::fs::write(
+            &script,
+            "grep -q '^p cnf 2 2$' \"$1\" && echo 's SATISFIABLE' && echo 'v -1 2 0'\n",
+        )
+        .unwrap();
+        let mut backend = ExternalBackend::new(&format!("sh {}", script.display()));
+        let a = backend.new_var();
+        let b = backend.new_var();
+        backend.add_clause(&[(a, true), (b, true)]);
+        assert!(backend.solve(&[(a, false)], &Limit::default()).unwrap());
+        assert!(!backend.value(a) && backend.value(b));
+        backend.add_clause(&[(b, false)]);
+        assert!(matches!(
+            backend.solve(&[(a, false)], &Limit::default()),
+            Err(SolverError::Output(_))
+        ));
+        std::fs::write(&script, "sleep 10\n").unwrap();
+        let start = Instant::now();
+        let limit = Limit {
+            deadline: Some(start + Duration::from_millis(100)),
+            cancel: None,
+        };
+        assert!(matches!(
+            backend.solve(&[], &limit),
+            Err(SolverError::Timeout)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let _ = std::fs::remove_file(&script);
+        let mut missing = ExternalBackend::new("/nonexistent/solver");
+        assert!(matches!(
+            missing.solve(&[], &Limit::default()),
+            Err(SolverError::Io(_))
+        ));
+    }
+}
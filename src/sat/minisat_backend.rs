@@ -0,0 +1,129 @@
+use super::{Limit, SatBackend, SolverError, SolverStats};
+use minisat::sys::*;
+use std::sync::mpsc::{self, RecvTimeoutError};
+
+/// 使用 MiniSat 求解
+///
+/// 直接使用 C 接口，以便在达到停止条件时从另一个线程中断求解。
+pub struct MinisatBackend {
+    ptr: *mut minisat_solver,
+    vars: usize,
+    /// 上一次可满足的求解中变量的取值
+    model: Vec<bool>,
+}
+
+/// 用于在看门狗线程中中断求解器；`minisat_interrupt` 只设置标志，可以在求解时从其它线程调用
+struct Interrupt(*mut minisat_solver);
+
+unsafe impl Send for Interrupt {}
+
+impl MinisatBackend {
+    pub fn new() -> Self {
+        let ptr = unsafe { minisat_new() };
+        // 关闭变量消去，否则增量求解时被消去的变量无法再出现在新的子句中
+        unsafe { minisat_eliminate(ptr, 1) };
+        Self {
+            ptr,
+            vars: 0,
+            model: vec![],
+        }
+    }
+
+    fn lit(&self, (var, positive): (usize, bool)) -> minisat_Lit {
+        unsafe { minisat_mkLit_args(var as minisat_Var, !positive as i32) }
+    }
+
+    /// 求解已经通过 `minisat_solve_addLit` 添加的假设，达到停止条件时中断
+    fn solve_limited(&mut self, limit: &Limit) -> minisat_lbool {
+        if limit.is_unlimited() {
+            return unsafe { minisat_limited_solve_commit(self.ptr) };
+        }
+        let (done, wait) = mpsc::channel::<()>();
+        let interrupt = Interrupt(self.ptr);
+        let watchdog_limit = limit.clone();
+        let watchdog = std::thread::spawn(move || {
+            let interrupt = interrupt;
+            let limit = watchdog_limit;
+            while let Err(RecvTimeoutError::Timeout) = wait.recv_timeout(limit.next_check()) {
+                if limit.check().is_err() {
+                    unsafe { minisat_interrupt(interrupt.0) };
+                    return;
+                }
+            }
+        });
+        let result = unsafe { minisat_limited_solve_commit(self.ptr) };
+        // 等待看门狗结束后再清除中断标志，避免影响之后的求解
+        let _ = done.send(());
+        let _ = watchdog.join();
+        unsafe { minisat_clearInterrupt(self.ptr) };
+        result
+    }
+}
+
+impl Default for MinisatBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MinisatBackend {
+    fn drop(&mut self) {
+        unsafe { minisat_delete(self.ptr) };
+    }
+}
+
+impl std::fmt::Debug for MinisatBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MinisatBackend")
+            .field("vars", &self.vars)
+            .finish()
+    }
+}
+
+impl SatBackend for MinisatBackend {
+    fn new_var(&mut self) -> usize {
+        unsafe { minisat_newVar(self.ptr) };
+        self.vars += 1;
+        self.vars - 1
+    }
+
+    fn add_clause(&mut self, lits: &[(usize, bool)]) {
+        unsafe { minisat_addClause_begin(self.ptr) };
+        for l in lits.iter() {
+            unsafe { minisat_addClause_addLit(self.ptr, self.lit(*l)) };
+        }
+        unsafe { minisat_addClause_commit(self.ptr) };
+    }
+
+    fn solve(&mut self, assumptions: &[(usize, bool)], limit: &Limit) -> Result<bool, SolverError> {
+        unsafe { minisat_solve_begin(self.ptr) };
+        for l in assumptions.iter() {
+            unsafe { minisat_solve_addLit(self.ptr, self.lit(*l)) };
+        }
+        let result = self.solve_limited(limit);
+        let (l_true, l_false) = unsafe { (minisat_get_l_True(), minisat_get_l_False()) };
+        if result == l_true {
+            self.model = (0..self.vars)
+                .map(|v| unsafe { minisat_modelValue_Var(self.ptr, v as minisat_Var) } == l_true)
+                .collect();
+            Ok(true)
+        } else if result == l_false {
+            Ok(false)
+        } else {
+            // 被中断时停止条件已经达到，`check` 总会返回对应的错误
+            limit.check()?;
+            Err(SolverError::Timeout)
+        }
+    }
+
+    fn value(&self, var: usize) -> bool {
+        self.model.get(var).copied().unwrap_or(false)
+    }
+
+    fn stats(&self) -> Option<SolverStats> {
+        Some(SolverStats {
+            conflicts: unsafe { minisat_num_conflicts(self.ptr) } as u64,
+            decisions: unsafe { minisat_num_decisions(self.ptr) } as u64,
+        })
+    }
+}
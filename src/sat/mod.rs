@@ -1,5 +1,13 @@
+mod backend;
 mod convert_sat;
+mod dimacs;
+mod external;
+mod minisat_backend;
 mod transformer;
+mod varisat_backend;
 
-pub use convert_sat::SATConverter;
-pub use transformer::convert_cnf;
+pub use backend::{Backend, Limit, SatBackend, SolverError, SolverStats, UnknownBackend};
+pub use convert_sat::{EncodingError, SATConverter};
+pub use dimacs::Dimacs;
+pub use minisat_backend::MinisatBackend;
+pub use transformer::{convert_cnf, convert_cnf_local, convert_tseitin_local};
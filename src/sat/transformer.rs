@@ -1,14 +1,60 @@
-use crate::bool_logic::{BinaryOp, PropExpr, UnaryOp};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::bool_logic::{BinaryOp, PropExpr, UnaryOp, Variable};
 
-/// 将任意逻辑表达式转换为 CNF 形式
+/// 将任意逻辑表达式转换为 CNF 形式，引入的无关变量在表达式内从 0 开始编号，见 `convert_cnf_local`
 pub fn convert_cnf(val: PropExpr) -> PropExpr {
-    PropExpr::chained_and(
-        flatten(conv_cnf(elim_not(elim_impl_eq(val))))
+    convert_cnf_local(val).0
+}
+
+/// 将任意逻辑表达式转换为 CNF 形式，引入的无关变量在表达式内从 0 开始编号，同时返回其数量
+///
+/// 可以在多个线程中分别转换，再按照固定的顺序通过 `SATConverter::add_local_clause` 加入求解器，
+/// 由求解器为无关变量编号，因此结果与依次转换相同。
+pub fn convert_cnf_local(val: PropExpr) -> (PropExpr, usize) {
+    let mut phantoms = 0;
+    let cnf = PropExpr::chained_and(
+        flatten(conv_cnf(elim_not(elim_impl_eq(val)), &mut phantoms))
             .into_iter()
             .map(PropExpr::chained_or)
             .collect(),
-    )
+    );
+    (cnf, phantoms)
+}
+
+/// 使用 Tseitin 变换将任意逻辑表达式转换为可满足性等价的 CNF 形式，引入的定义变量在表达式内从 0
+/// 开始编号，同时返回其数量，见 `convert_cnf_local`
+///
+/// 每个非文字的子表达式都会引入一个定义变量（`Variable::Phantom`），子句数量与表达式大小成线性关系。
+/// 由于 NNF 中所有子表达式都是正极性的，采用 Plaisted–Greenbaum 变体，只生成定义变量蕴含子表达式一侧的子句。
+pub fn convert_tseitin_local(val: PropExpr) -> (PropExpr, usize) {
+    let mut phantoms = 0;
+    let mut clauses = vec![];
+    tseitin_assert(elim_not(elim_impl_eq(val)), &mut clauses, &mut phantoms);
+    let cnf = PropExpr::chained_and(clauses.into_iter().map(PropExpr::chained_or).collect());
+    (cnf, phantoms)
+}
+
+/// 将表达式中在表达式内编号的无关变量的编号增加 `base`
+pub(super) fn offset_phantoms(e: PropExpr, base: usize) -> PropExpr {
+    match e {
+        PropExpr::Variable(Variable::Phantom(i)) => Variable::Phantom(base + i).into(),
+        v @ PropExpr::Variable(_) => v,
+        PropExpr::Unary(op, rhs) => PropExpr::Unary(op, Box::new(offset_phantoms(*rhs, base))),
+        PropExpr::Binary(lhs, op, rhs) => PropExpr::Binary(
+            Box::new(offset_phantoms(*lhs, base)),
+            op,
+            Box::new(offset_phantoms(*rhs, base)),
+        ),
+        PropExpr::ChainedBinary(op, vals) => PropExpr::ChainedBinary(
+            op,
+            vals.into_iter().map(|v| offset_phantoms(v, base)).collect(),
+        ),
+    }
+}
+
+/// 生成新的无关变量，`phantoms` 为表达式内已经引入的数量
+fn new_phantom(phantoms: &mut usize) -> Variable {
+    *phantoms += 1;
+    Variable::Phantom(*phantoms - 1)
 }
 
 /// (1) 消除表达式的推理和等价运算符
@@ -27,16 +73,14 @@ fn elim_impl_eq(val: PropExpr) -> PropExpr {
             (!lhs.clone() | rhs.clone()) & (lhs | !rhs)
         }
         // recursion case
-        PropExpr::Unary(op, rhs) => match *rhs {
-            _ => PropExpr::Unary(op, Box::new(elim_impl_eq(*rhs))),
-        },
+        PropExpr::Unary(op, rhs) => PropExpr::Unary(op, Box::new(elim_impl_eq(*rhs))),
         PropExpr::Binary(lhs, op, rhs) => PropExpr::Binary(
             Box::new(elim_impl_eq(*lhs)),
             op,
             Box::new(elim_impl_eq(*rhs)),
         ),
         PropExpr::ChainedBinary(op, vals) => {
-            PropExpr::ChainedBinary(op, vals.into_iter().map(|v| elim_impl_eq(v)).collect())
+            PropExpr::ChainedBinary(op, vals.into_iter().map(elim_impl_eq).collect())
         }
         PropExpr::Variable(_) => val,
     }
@@ -68,14 +112,12 @@ fn elim_not(val: PropExpr) -> PropExpr {
         },
         // recursion case
         #[allow(unreachable_patterns)] // guard this case for safety
-        PropExpr::Unary(op, rhs) => match *rhs {
-            _ => PropExpr::Unary(op, Box::new(elim_not(*rhs))),
-        },
+        PropExpr::Unary(op, rhs) => PropExpr::Unary(op, Box::new(elim_not(*rhs))),
         PropExpr::Binary(lhs, op, rhs) => {
             PropExpr::Binary(Box::new(elim_not(*lhs)), op, Box::new(elim_not(*rhs)))
         }
         PropExpr::ChainedBinary(op, vals) => {
-            PropExpr::ChainedBinary(op, vals.into_iter().map(|v| elim_not(v)).collect())
+            PropExpr::ChainedBinary(op, vals.into_iter().map(elim_not).collect())
         }
         PropExpr::Variable(_) => val,
     }
@@ -86,7 +128,7 @@ fn elim_not(val: PropExpr) -> PropExpr {
 /// 返回值是一系列析取式，彼此之间是合取关系
 ///
 /// see https://www.cs.jhu.edu/~jason/tutorials/convert-to-CNF.html
-fn conv_cnf(e: PropExpr) -> Vec<PropExpr> {
+fn conv_cnf(e: PropExpr, phantoms: &mut usize) -> Vec<PropExpr> {
     //  CONVERT(φ):   // returns a CNF formula equivalent to φ
 
     // // Any syntactically valid propositional formula φ must fall into
@@ -104,14 +146,15 @@ fn conv_cnf(e: PropExpr) -> Vec<PropExpr> {
         //    where all the Pi and Qi are disjunctions of literals.
         //    So return P1 ^ P2 ^ ... ^ Pm ^ Q1 ^ Q2 ^ ... ^ Qn.
         PropExpr::Binary(lhs, BinaryOp::Conjunction, rhs) => {
-            let mut left = conv_cnf(*lhs);
-            let mut right = conv_cnf(*rhs);
+            let mut left = conv_cnf(*lhs, phantoms);
+            let mut right = conv_cnf(*rhs, phantoms);
             left.append(&mut right);
             left
         }
-        PropExpr::ChainedBinary(BinaryOp::Conjunction, vals) => {
-            vals.into_iter().flat_map(conv_cnf).collect()
-        }
+        PropExpr::ChainedBinary(BinaryOp::Conjunction, vals) => vals
+            .into_iter()
+            .flat_map(|v| conv_cnf(v, phantoms))
+            .collect(),
         // If φ has the form P v Q, then:
         //    CONVERT(P) must have the form P1 ^ P2 ^ ... ^ Pm, and
         //    CONVERT(Q) must have the form Q1 ^ Q2 ^ ... ^ Qn,
@@ -123,21 +166,20 @@ fn conv_cnf(e: PropExpr) -> Vec<PropExpr> {
         //              ...
         //            ^ (Pm v Q1) ^ (Pm v Q2) ^ ... ^ (Pm v Qn)
         PropExpr::Binary(lhs, BinaryOp::Disjunction, rhs) => {
-            let left = conv_cnf(*lhs);
-            let right = conv_cnf(*rhs);
+            let left = conv_cnf(*lhs, phantoms);
+            let right = conv_cnf(*rhs, phantoms);
             let mut ret = vec![];
 
             if left.len() > 1 && right.len() > 1 {
-                static OBJECT_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-                use crate::bool_logic::Variable;
-                let id = OBJECT_COUNTER.fetch_add(1, Ordering::SeqCst);
-                ret.append(&mut conv_cnf(elim_not(elim_impl_eq(
-                    Variable::Phantom(id) >> PropExpr::chained_and(left),
-                ))));
-                ret.append(&mut conv_cnf(elim_not(elim_impl_eq(
-                    !Variable::Phantom(id) >> PropExpr::chained_and(right),
-                ))));
+                let phantom = new_phantom(phantoms);
+                ret.append(&mut conv_cnf(
+                    elim_not(elim_impl_eq(phantom >> PropExpr::chained_and(left))),
+                    phantoms,
+                ));
+                ret.append(&mut conv_cnf(
+                    elim_not(elim_impl_eq(!phantom >> PropExpr::chained_and(right))),
+                    phantoms,
+                ));
             } else {
                 for l in left.iter() {
                     for r in right.iter() {
@@ -149,13 +191,17 @@ fn conv_cnf(e: PropExpr) -> Vec<PropExpr> {
         }
         PropExpr::ChainedBinary(BinaryOp::Disjunction, vals) => {
             if let [f, rest @ ..] = vals.as_slice() {
-                if rest.len() > 0 {
-                    conv_cnf(PropExpr::or(
-                        f.clone(),
-                        PropExpr::chained_and(conv_cnf(PropExpr::chained_or(
-                            rest.into_iter().cloned().collect(),
-                        ))),
-                    ))
+                if !rest.is_empty() {
+                    conv_cnf(
+                        PropExpr::or(
+                            f.clone(),
+                            PropExpr::chained_and(conv_cnf(
+                                PropExpr::chained_or(rest.to_vec()),
+                                phantoms,
+                            )),
+                        ),
+                        phantoms,
+                    )
                 } else {
                     vec![f.clone()]
                 }
@@ -195,7 +241,7 @@ fn flatten_single(expr: PropExpr) -> Vec<PropExpr> {
             .flat_map(|v| flatten_single(*v))
             .collect(),
         PropExpr::ChainedBinary(BinaryOp::Disjunction, exprs) => {
-            exprs.into_iter().flat_map(|v| flatten_single(v)).collect()
+            exprs.into_iter().flat_map(flatten_single).collect()
         }
         v @ PropExpr::Unary(UnaryOp::Negation, _) => vec![v],
         v @ PropExpr::Variable(_) => vec![v],
@@ -210,6 +256,81 @@ fn flatten_single(expr: PropExpr) -> Vec<PropExpr> {
     }
 }
 
+/// (T1) 将 NNF 表达式中连续的同种运算展开为运算数列表
+fn operands(e: PropExpr, op: BinaryOp, ret: &mut Vec<PropExpr>) {
+    match e {
+        PropExpr::Binary(lhs, o, rhs) if o == op => {
+            operands(*lhs, op, ret);
+            operands(*rhs, op, ret);
+        }
+        PropExpr::ChainedBinary(o, vals) if o == op => {
+            for v in vals.into_iter() {
+                operands(v, op, ret);
+            }
+        }
+        e => ret.push(e),
+    }
+}
+
+/// (T2) 断言 NNF 表达式成立，生成对应的析取子句
+fn tseitin_assert(e: PropExpr, clauses: &mut Vec<Vec<PropExpr>>, phantoms: &mut usize) {
+    match e {
+        PropExpr::Binary(_, BinaryOp::Conjunction, _)
+        | PropExpr::ChainedBinary(BinaryOp::Conjunction, _) => {
+            let mut vals = vec![];
+            operands(e, BinaryOp::Conjunction, &mut vals);
+            for v in vals.into_iter() {
+                tseitin_assert(v, clauses, phantoms);
+            }
+        }
+        PropExpr::Binary(_, BinaryOp::Disjunction, _)
+        | PropExpr::ChainedBinary(BinaryOp::Disjunction, _) => {
+            let mut vals = vec![];
+            operands(e, BinaryOp::Disjunction, &mut vals);
+            let clause = vals
+                .into_iter()
+                .map(|v| tseitin_lit(v, clauses, phantoms))
+                .collect();
+            clauses.push(clause);
+        }
+        lit @ PropExpr::Variable(_) | lit @ PropExpr::Unary(_, _) => clauses.push(vec![lit]),
+        // 其它运算符应当已经被消除
+        _ => unreachable!(),
+    }
+}
+
+/// (T3) 获得代表 NNF 表达式的文字，非文字的表达式将引入定义变量
+fn tseitin_lit(e: PropExpr, clauses: &mut Vec<Vec<PropExpr>>, phantoms: &mut usize) -> PropExpr {
+    match e {
+        PropExpr::Binary(_, BinaryOp::Conjunction, _)
+        | PropExpr::ChainedBinary(BinaryOp::Conjunction, _) => {
+            let def = new_phantom(phantoms);
+            let mut vals = vec![];
+            operands(e, BinaryOp::Conjunction, &mut vals);
+            for v in vals.into_iter() {
+                let lit = tseitin_lit(v, clauses, phantoms);
+                clauses.push(vec![!def, lit]);
+            }
+            def.into()
+        }
+        PropExpr::Binary(_, BinaryOp::Disjunction, _)
+        | PropExpr::ChainedBinary(BinaryOp::Disjunction, _) => {
+            let def = new_phantom(phantoms);
+            let mut vals = vec![];
+            operands(e, BinaryOp::Disjunction, &mut vals);
+            let mut clause = vec![!def];
+            for v in vals.into_iter() {
+                clause.push(tseitin_lit(v, clauses, phantoms));
+            }
+            clauses.push(clause);
+            def.into()
+        }
+        lit @ PropExpr::Variable(_) | lit @ PropExpr::Unary(_, _) => lit,
+        // 其它运算符应当已经被消除
+        _ => unreachable!(),
+    }
+}
+
 #[cfg(test)]
 mod test {
     mod elim_impl_eq {
@@ -279,34 +400,33 @@ mod test {
         #[test]
         fn plain() {
             // (a) p
-            assert_eq!(conv_cnf(V(1).into()), vec![V(1).into()]);
+            assert_eq!(conv_cnf(V(1).into(), &mut 0), vec![V(1).into()]);
             // (b) A | B
-            assert_eq!(conv_cnf(V(1) | V(2)), vec![V(1) | V(2)]);
+            assert_eq!(conv_cnf(V(1) | V(2), &mut 0), vec![V(1) | V(2)]);
             assert_eq!(
-                conv_cnf(V(1) | (V(2) & V(3))),
+                conv_cnf(V(1) | (V(2) & V(3)), &mut 0),
                 vec![V(1) | V(2), V(1) | V(3)]
             );
             assert_eq!(
-                conv_cnf(PropExpr::chained_or(vec![
-                    V(1).into(),
-                    V(2) & V(3),
-                    V(4) | V(5)
-                ])),
+                conv_cnf(
+                    PropExpr::chained_or(vec![V(1).into(), V(2) & V(3), V(4) | V(5)]),
+                    &mut 0
+                ),
                 vec![V(1) | (V(2) | (V(4) | V(5))), V(1) | (V(3) | (V(4) | V(5)))]
             );
             // (c) A & B
             assert_eq!(
-                conv_cnf(V(1) & (V(2) | V(3))),
+                conv_cnf(V(1) & (V(2) | V(3)), &mut 0),
                 vec![V(1).into(), V(2) | V(3)]
             );
             // (d) !p
-            assert_eq!(conv_cnf(!V(1)), vec![!V(1)]);
+            assert_eq!(conv_cnf(!V(1), &mut 0), vec![!V(1)]);
         }
 
         #[test]
         fn extra() {
             assert_eq!(
-                conv_cnf((!V(0) | (!V(1) & !V(2))) & (V(0) | V(1) | V(2))),
+                conv_cnf((!V(0) | (!V(1) & !V(2))) & (V(0) | V(1) | V(2)), &mut 0),
                 vec![!V(0) | !V(1), !V(0) | !V(2), V(0) | V(1) | V(2)]
             );
         }
@@ -358,4 +478,103 @@ mod test {
             );
         }
     }
+
+    mod convert_tseitin {
+        use super::super::*;
+        use crate::bool_logic::Variable::{self, And as V};
+
+        /// 验证对于原变量的每一种取值，原表达式成立当且仅当存在定义变量的取值使得 CNF 成立
+        fn equisatisfiable(expr: PropExpr, vars: &[Variable]) {
+            let (cnf, _) = convert_tseitin_local(expr.clone());
+            let mut phantoms = vec![];
+            if let PropExpr::ChainedBinary(BinaryOp::Conjunction, clauses) = &cnf {
+                for clause in clauses.iter() {
+                    let mut lits = vec![];
+                    operands(clause.clone(), BinaryOp::Disjunction, &mut lits);
+                    for lit in lits.into_iter() {
+                        let lit = match lit {
+                            PropExpr::Unary(_, v) => *v,
+                            v => v,
+                        };
+                        if let PropExpr::Variable(v @ Variable::Phantom(_)) = lit {
+                            phantoms.push(v);
+                        }
+                    }
+                }
+            } else {
+                panic!("result is not a conjunction");
+            }
+            phantoms.sort_by_key(|v| format!("{:?}", v));
+            phantoms.dedup();
+            for i in 0..(1 << vars.len()) {
+                let model: Vec<Variable> = (0..vars.len())
+                    .filter(|j| i & (1 << j) != 0)
+                    .map(|j| vars[j])
+                    .collect();
+                let sat = (0..(1 << phantoms.len())).any(|k| {
+                    let mut model = model.clone();
+                    model.extend(
+                        (0..phantoms.len())
+                            .filter(|j| k & (1 << j) != 0)
+                            .map(|j| phantoms[j]),
+                    );
+                    cnf._validate(&model)
+                });
+                assert_eq!(expr._validate(&model), sat, "model {:?}", model);
+            }
+        }
+
+        #[test]
+        fn plain() {
+            assert_eq!(
+                convert_tseitin_local(V(1) | V(2)).0,
+                PropExpr::chained_and(vec![PropExpr::chained_or(vec![V(1).into(), V(2).into()])])
+            );
+            assert_eq!(
+                convert_tseitin_local(V(1) & !V(2)).0,
+                PropExpr::chained_and(vec![
+                    PropExpr::chained_or(vec![V(1).into()]),
+                    PropExpr::chained_or(vec![!V(2)]),
+                ])
+            );
+        }
+
+        #[test]
+        fn nested() {
+            let vars = [V(0), V(1), V(2), V(3)];
+            equisatisfiable(V(0) | (V(1) & V(2)), &vars);
+            equisatisfiable((V(0) & V(1)) | (V(2) & V(3)), &vars);
+            equisatisfiable(V(0) << ((V(1) & V(2)) | !V(3)), &vars);
+            equisatisfiable(PropExpr::biconditional(V(0).into(), !V(1) & !V(2)), &vars);
+            equisatisfiable(
+                PropExpr::chained_or(vec![
+                    PropExpr::chained_and(vec![V(0).into(), !V(1), V(2).into()]),
+                    PropExpr::chained_and(vec![!V(0), V(1).into(), V(3).into()]),
+                    !(V(2) | V(3)),
+                ]),
+                &vars,
+            );
+        }
+
+        #[test]
+        fn local() {
+            let expr = (V(0) & V(1)) | (V(2) & V(3));
+            let (cnf, phantoms) = convert_tseitin_local(expr);
+            assert_eq!(phantoms, 2);
+            // 只改变无关变量的编号
+            assert_eq!(
+                offset_phantoms(cnf, 5),
+                PropExpr::chained_and(vec![
+                    PropExpr::chained_or(vec![!Variable::Phantom(5), V(0).into()]),
+                    PropExpr::chained_or(vec![!Variable::Phantom(5), V(1).into()]),
+                    PropExpr::chained_or(vec![!Variable::Phantom(6), V(2).into()]),
+                    PropExpr::chained_or(vec![!Variable::Phantom(6), V(3).into()]),
+                    PropExpr::chained_or(vec![
+                        Variable::Phantom(5).into(),
+                        Variable::Phantom(6).into()
+                    ]),
+                ])
+            );
+        }
+    }
 }
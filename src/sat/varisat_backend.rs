@@ -0,0 +1,76 @@
+use super::{Limit, SatBackend, SolverError};
+use varisat::{ExtendFormula, Lit, Solver};
+
+/// 使用 Varisat 求解
+///
+/// Varisat 不支持中断，只在开始求解前检查停止条件。
+pub struct VarisatBackend {
+    solver: Solver<'static>,
+    vars: usize,
+    /// 上一次可满足的求解中变量的取值
+    model: Vec<bool>,
+}
+
+impl VarisatBackend {
+    pub fn new() -> Self {
+        Self {
+            solver: Solver::new(),
+            vars: 0,
+            model: vec![],
+        }
+    }
+}
+
+impl Default for VarisatBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for VarisatBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VarisatBackend")
+            .field("vars", &self.vars)
+            .finish()
+    }
+}
+
+fn lit((var, positive): (usize, bool)) -> Lit {
+    Lit::from_index(var, positive)
+}
+
+impl SatBackend for VarisatBackend {
+    fn new_var(&mut self) -> usize {
+        self.solver.new_var();
+        self.vars += 1;
+        self.vars - 1
+    }
+
+    fn add_clause(&mut self, lits: &[(usize, bool)]) {
+        let clause: Vec<Lit> = lits.iter().map(|l| lit(*l)).collect();
+        self.solver.add_clause(&clause);
+    }
+
+    fn solve(&mut self, assumptions: &[(usize, bool)], limit: &Limit) -> Result<bool, SolverError> {
+        limit.check()?;
+        let lits: Vec<Lit> = assumptions.iter().map(|l| lit(*l)).collect();
+        self.solver.assume(&lits);
+        let sat = self
+            .solver
+            .solve()
+            .map_err(|e| SolverError::Internal(e.to_string()))?;
+        if sat {
+            self.model = vec![false; self.vars];
+            for l in self.solver.model().unwrap_or_default() {
+                if l.index() < self.vars {
+                    self.model[l.index()] = l.is_positive();
+                }
+            }
+        }
+        Ok(sat)
+    }
+
+    fn value(&self, var: usize) -> bool {
+        self.model.get(var).copied().unwrap_or(false)
+    }
+}
@@ -0,0 +1,151 @@
+//! 每个尺寸限制的求解统计
+
+use crate::sat::SolverStats;
+use serde::Serialize;
+use std::time::Duration;
+
+/// 某个规则生成器生成的规则数量
+#[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+pub struct RuleCount {
+    /// 规则生成器的名称，如 `ExampleEnforcer`
+    pub enforcer: &'static str,
+    /// 生成的规则数量，包括只在当前尺寸限制下成立的规则
+    pub rules: usize,
+}
+
+/// 一个尺寸限制的求解统计
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct BoundStats {
+    /// 尺寸限制
+    pub bound: usize,
+    /// 是否为允许例子不被满足时求得最小代价之和的阶段，该阶段总在最大的尺寸限制下求解
+    pub min_errors: bool,
+    /// 最后一次求解时不被满足的例子的代价之和的上界
+    pub max_errors: Option<usize>,
+    /// 是否求得公式，超时或出错时为 `None`
+    pub satisfiable: Option<bool>,
+    /// 每个规则生成器生成的规则数量，按照 `ContextEnforcer` 中的顺序
+    pub rules: Vec<RuleCount>,
+    /// 编码后求解器中的变量数量，增量求解时包括之前的尺寸限制
+    pub variables: usize,
+    /// 编码后求解器中的子句数量，增量求解时包括之前的尺寸限制
+    pub clauses: usize,
+    /// 生成规则、转换为 CNF 并加入求解器所用的时间（秒）
+    pub encode_secs: f64,
+    /// 求解所用的时间（秒），包括求得其它公式的求解
+    pub solve_secs: f64,
+    /// 求解次数
+    pub solves: usize,
+    /// 求解中的冲突次数，求解器不支持时为 `None`
+    pub conflicts: Option<u64>,
+    /// 求解中的决策次数，求解器不支持时为 `None`
+    pub decisions: Option<u64>,
+}
+
+impl BoundStats {
+    pub fn new(bound: usize) -> Self {
+        Self {
+            bound,
+            ..Self::default()
+        }
+    }
+
+    /// 累加规则数量，同名的规则生成器合并计数
+    pub fn add_rules(&mut self, enforcer: &'static str, rules: usize) {
+        match self.rules.iter_mut().find(|r| r.enforcer == enforcer) {
+            Some(count) => count.rules += rules,
+            None => self.rules.push(RuleCount { enforcer, rules }),
+        }
+    }
+
+    pub fn add_encode_time(&mut self, time: Duration) {
+        self.encode_secs += time.as_secs_f64();
+    }
+
+    /// 记录一次求解，`before` 与 `after` 为求解前后求解器的累计统计
+    pub fn add_solve(
+        &mut self,
+        time: Duration,
+        before: Option<SolverStats>,
+        after: Option<SolverStats>,
+    ) {
+        self.solve_secs += time.as_secs_f64();
+        self.solves += 1;
+        if let (Some(before), Some(after)) = (before, after) {
+            *self.conflicts.get_or_insert(0) += after.conflicts - before.conflicts;
+            *self.decisions.get_or_insert(0) += after.decisions - before.decisions;
+        }
+    }
+}
+
+impl std::fmt::Display for BoundStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "统计 n = {}", self.bound)?;
+        if self.min_errors {
+            f.write_str("（求最小代价）")?;
+        }
+        let result = match self.satisfiable {
+            Some(true) => "可满足",
+            Some(false) => "不可满足",
+            None => "未完成",
+        };
+        write!(
+            f,
+            "：{}，变量数 {}，子句数 {}，编码 {:.1} ms，求解 {:.1} ms（{} 次）",
+            result,
+            self.variables,
+            self.clauses,
+            self.encode_secs * 1000.0,
+            self.solve_secs * 1000.0,
+            self.solves
+        )?;
+        if let (Some(conflicts), Some(decisions)) = (self.conflicts, self.decisions) {
+            write!(f, "，冲突 {}，决策 {}", conflicts, decisions)?;
+        }
+        f.write_str("；规则数")?;
+        for (i, count) in self.rules.iter().enumerate() {
+            let sep = if i == 0 { " " } else { "，" };
+            write!(f, "{}{} {}", sep, count.enforcer, count.rules)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn accumulate() {
+        let mut stats = BoundStats::new(3);
+        stats.add_rules("LTLSubtreeEnforcer", 4);
+        stats.add_rules("ExampleEnforcer", 10);
+        stats.add_rules("LTLSubtreeEnforcer", 2);
+        assert_eq!(
+            stats.rules,
+            vec![
+                RuleCount {
+                    enforcer: "LTLSubtreeEnforcer",
+                    rules: 6
+                },
+                RuleCount {
+                    enforcer: "ExampleEnforcer",
+                    rules: 10
+                }
+            ]
+        );
+        let stats_at = |conflicts, decisions| {
+            Some(SolverStats {
+                conflicts,
+                decisions,
+            })
+        };
+        stats.add_solve(Duration::from_millis(2), stats_at(5, 20), stats_at(8, 30));
+        stats.add_solve(Duration::from_millis(1), stats_at(8, 30), stats_at(9, 31));
+        assert_eq!((stats.conflicts, stats.decisions), (Some(4), Some(11)));
+        assert_eq!(stats.solves, 2);
+        stats.add_solve(Duration::from_millis(1), None, None);
+        assert_eq!(stats.solves, 3);
+        assert_eq!(stats.conflicts, Some(4));
+    }
+}
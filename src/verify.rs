@@ -0,0 +1,53 @@
+//! 检验公式在输入例子上的结果，无需再次求解
+
+use crate::{Input, LtlNode};
+
+/// 公式在例子上的检验结果，记录例子的序号
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Verification {
+    /// 被接受的正例
+    pub accepted_pos: Vec<usize>,
+    /// 被拒绝的正例
+    pub rejected_pos: Vec<usize>,
+    /// 被接受的反例
+    pub accepted_neg: Vec<usize>,
+    /// 被拒绝的反例
+    pub rejected_neg: Vec<usize>,
+}
+
+impl Verification {
+    /// 公式是否接受所有正例且拒绝所有反例
+    pub fn is_consistent(&self) -> bool {
+        self.rejected_pos.is_empty() && self.accepted_neg.is_empty()
+    }
+}
+
+/// 在所有例子的时间 `0` 上求值公式，要求循环起点合法（见 `Input::validate`）；空序列总是不被接受
+pub fn verify(input: &Input, formula: &LtlNode) -> Verification {
+    let mut result = Verification::default();
+    for (i, (trace, lasso)) in input
+        .traces_pos()
+        .iter()
+        .zip(input.lassos_pos())
+        .enumerate()
+    {
+        if formula.eval_lasso(trace, *lasso, 0) {
+            result.accepted_pos.push(i);
+        } else {
+            result.rejected_pos.push(i);
+        }
+    }
+    for (i, (trace, lasso)) in input
+        .traces_neg()
+        .iter()
+        .zip(input.lassos_neg())
+        .enumerate()
+    {
+        if formula.eval_lasso(trace, *lasso, 0) {
+            result.accepted_neg.push(i);
+        } else {
+            result.rejected_neg.push(i);
+        }
+    }
+    result
+}